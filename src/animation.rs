@@ -0,0 +1,255 @@
+use nalgebra_glm::Vec3;
+use std::f32::consts::PI;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::codecs::png::PngEncoder;
+use image::{ColorType, Delay, Frame};
+use crate::camera::Camera;
+use crate::framebuffer::Framebuffer;
+use crate::path::CameraPath;
+
+// Cómo se mueve la cámara a lo largo de la animación
+pub enum CameraMotion {
+    Turntable { turns: f32 },  // Gira alrededor del centro de la escena
+    Path(CameraPath),          // Sigue un recorrido de keyframes
+}
+
+// Cómo se mueve la luz
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LightMotion {
+    Still,
+    Orbit,  // Da una vuelta completa alrededor del centro inicial de la cámara, a la misma altura
+}
+
+pub struct Animation {
+    pub fps: f32,
+    pub duration: f32,  // Segundos
+    pub camera: CameraMotion,
+    pub light: LightMotion,
+}
+
+impl Animation {
+    pub fn frame_count(&self) -> usize {
+        (self.duration * self.fps).round().max(1.0) as usize
+    }
+
+    // Segundos desde el inicio hasta el cuadro `frame`
+    pub fn time(&self, frame: usize) -> f32 {
+        frame as f32 / self.fps
+    }
+
+    // Cámara y posición de la luz del cuadro `frame`. Solo dependen del número de cuadro,
+    // así que la misma escena (y semilla) produce siempre los mismos cuadros
+    pub fn frame(&self, frame: usize, camera: &Camera, light_position: Vec3) -> (Camera, Vec3) {
+        let time = self.time(frame);
+        let progress = if self.duration > 0.0 { time / self.duration } else { 0.0 };
+        // La luz gira alrededor de un punto fijo aunque el recorrido mueva el centro de la cámara
+        let pivot = camera.center;
+
+        let mut camera = camera.clone();
+        match &self.camera {
+            CameraMotion::Turntable { turns } => camera.orbit(2.0 * PI * turns * progress, 0.0),
+            CameraMotion::Path(path) => {
+                path.apply(&mut camera, time);
+            }
+        }
+
+        let light_position = match self.light {
+            LightMotion::Still => light_position,
+            LightMotion::Orbit => {
+                let offset = light_position - pivot;
+                let angle = 2.0 * PI * progress;
+                pivot + Vec3::new(
+                    offset.x * angle.cos() - offset.z * angle.sin(),
+                    offset.y,
+                    offset.x * angle.sin() + offset.z * angle.cos(),
+                )
+            }
+        };
+
+        (camera, light_position)
+    }
+}
+
+/// Destino de los cuadros: PNG numerados en una carpeta, GIF animado o APNG según `output`
+/// (`.gif`, `.apng` o cualquier otra cosa como nombre de carpeta).
+pub enum AnimationWriter {
+    Frames { directory: String, next: usize },
+    Gif { path: String, encoder: Box<GifEncoder<BufWriter<File>>>, delay_ms: u32 },
+    Apng { path: String, fps: f32, frames: Vec<Vec<u8>>, size: (u32, u32) },
+}
+
+impl AnimationWriter {
+    pub fn create(output: &str, fps: f32) -> Result<AnimationWriter, String> {
+        let extension = output.rsplit_once('.').map(|(_, ext)| ext.to_lowercase()).unwrap_or_default();
+        match extension.as_str() {
+            "gif" => {
+                let file = File::create(output).map_err(|e| format!("No se pudo crear {}: {}", output, e))?;
+                let mut encoder = GifEncoder::new(BufWriter::new(file));
+                encoder.set_repeat(Repeat::Infinite).map_err(|e| e.to_string())?;
+                Ok(AnimationWriter::Gif { path: output.to_string(), encoder: Box::new(encoder), delay_ms: (1000.0 / fps).round() as u32 })
+            }
+            "apng" => Ok(AnimationWriter::Apng { path: output.to_string(), fps, frames: Vec::new(), size: (0, 0) }),
+            _ => {
+                fs::create_dir_all(output).map_err(|e| format!("No se pudo crear {}: {}", output, e))?;
+                Ok(AnimationWriter::Frames { directory: output.to_string(), next: 0 })
+            }
+        }
+    }
+
+    pub fn push(&mut self, framebuffer: &Framebuffer) -> Result<(), String> {
+        match self {
+            AnimationWriter::Frames { directory, next } => {
+                let file = format!("{}/frame_{:04}.png", directory, next);
+                *next += 1;
+                framebuffer.save(&file).map_err(|e| format!("No se pudo guardar {}: {}", file, e))
+            }
+            AnimationWriter::Gif { encoder, delay_ms, .. } => {
+                // El codificador reduce cada cuadro a una paleta de 256 colores (NeuQuant)
+                let rgba = image::DynamicImage::ImageRgb8(framebuffer.to_image()).to_rgba8();
+                let frame = Frame::from_parts(rgba, 0, 0, Delay::from_numer_denom_ms(*delay_ms, 1));
+                encoder.encode_frame(frame).map_err(|e| e.to_string())
+            }
+            AnimationWriter::Apng { frames, size, .. } => {
+                let mut png = Vec::new();
+                let image = framebuffer.to_image();
+                PngEncoder::new(&mut png)
+                    .encode(image.as_raw(), image.width(), image.height(), ColorType::Rgb8)
+                    .map_err(|e| e.to_string())?;
+                *size = (image.width(), image.height());
+                frames.push(png);
+                Ok(())
+            }
+        }
+    }
+
+    // Termina el archivo; devuelve dónde quedó la animación
+    pub fn finish(self) -> Result<String, String> {
+        match self {
+            AnimationWriter::Frames { directory, .. } => Ok(directory),
+            AnimationWriter::Gif { path, encoder, .. } => {
+                // El GIF se cierra al soltar el codificador
+                drop(encoder);
+                Ok(path)
+            }
+            AnimationWriter::Apng { path, fps, frames, size } => {
+                write_apng(&path, fps, &frames, size)?;
+                Ok(path)
+            }
+        }
+    }
+}
+
+// Arma un PNG animado a partir de los PNG de cada cuadro: el primero va como IDAT normal
+// (los visores sin APNG lo muestran como imagen fija) y el resto como fdAT
+fn write_apng(path: &str, fps: f32, frames: &[Vec<u8>], (width, height): (u32, u32)) -> Result<(), String> {
+    let mut out = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+    let mut sequence = 0u32;
+    let delay_ms = (1000.0 / fps).round() as u16;
+
+    for (index, png) in frames.iter().enumerate() {
+        let chunks = png_chunks(png)?;
+
+        if index == 0 {
+            let (_, ihdr) = chunks.iter().find(|(kind, _)| kind == b"IHDR").ok_or("PNG sin IHDR")?;
+            write_chunk(&mut out, b"IHDR", ihdr);
+
+            let mut actl = Vec::new();
+            actl.extend_from_slice(&(frames.len() as u32).to_be_bytes());
+            actl.extend_from_slice(&0u32.to_be_bytes());  // Repetir para siempre
+            write_chunk(&mut out, b"acTL", &actl);
+        }
+
+        let mut fctl = Vec::new();
+        fctl.extend_from_slice(&sequence.to_be_bytes());
+        fctl.extend_from_slice(&width.to_be_bytes());
+        fctl.extend_from_slice(&height.to_be_bytes());
+        fctl.extend_from_slice(&0u32.to_be_bytes());  // x
+        fctl.extend_from_slice(&0u32.to_be_bytes());  // y
+        fctl.extend_from_slice(&delay_ms.to_be_bytes());
+        fctl.extend_from_slice(&1000u16.to_be_bytes());
+        fctl.extend_from_slice(&[0, 0]);  // Sin dispose ni blend
+        write_chunk(&mut out, b"fcTL", &fctl);
+        sequence += 1;
+
+        for (_, data) in chunks.iter().filter(|(kind, _)| kind == b"IDAT") {
+            if index == 0 {
+                write_chunk(&mut out, b"IDAT", data);
+            } else {
+                let mut fdat = sequence.to_be_bytes().to_vec();
+                fdat.extend_from_slice(data);
+                write_chunk(&mut out, b"fdAT", &fdat);
+                sequence += 1;
+            }
+        }
+    }
+
+    write_chunk(&mut out, b"IEND", &[]);
+    let mut file = File::create(path).map_err(|e| format!("No se pudo crear {}: {}", path, e))?;
+    file.write_all(&out).map_err(|e| format!("No se pudo guardar {}: {}", path, e))
+}
+
+// Chunk de un PNG: tipo y datos
+type Chunk<'a> = ([u8; 4], &'a [u8]);
+
+// Separa un PNG en sus chunks
+fn png_chunks(png: &[u8]) -> Result<Vec<Chunk<'_>>, String> {
+    let mut chunks = Vec::new();
+    let mut offset = 8;
+    while offset + 12 <= png.len() {
+        let length = u32::from_be_bytes(png[offset..offset + 4].try_into().unwrap()) as usize;
+        let kind: [u8; 4] = png[offset + 4..offset + 8].try_into().unwrap();
+        let data = png.get(offset + 8..offset + 8 + length).ok_or("PNG truncado")?;
+        chunks.push((kind, data));
+        offset += 12 + length;
+    }
+    Ok(chunks)
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+// CRC-32 de PNG (polinomio 0xEDB88320)
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::Keyframe;
+
+    #[test]
+    fn light_orbits_a_fixed_center() {
+        let camera = Camera::new(Vec3::new(0.0, 2.0, 10.0), Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        let path = CameraPath { keyframes: vec![
+            Keyframe { time: 0.0, eye: Vec3::new(0.0, 2.0, 10.0), center: Vec3::new(0.0, 0.0, 0.0), fov: camera.fov },
+            Keyframe { time: 4.0, eye: Vec3::new(8.0, 2.0, 10.0), center: Vec3::new(8.0, 0.0, 0.0), fov: camera.fov },
+        ], ..CameraPath::default() };
+        let animation = Animation { fps: 1.0, duration: 4.0, camera: CameraMotion::Path(path), light: LightMotion::Orbit };
+        let light = Vec3::new(3.0, 5.0, 0.0);
+
+        for frame in 0..=4 {
+            let (_, position) = animation.frame(frame, &camera, light);
+            // Siempre a la misma distancia y altura del centro inicial, aunque la cámara se haya corrido
+            assert!((Vec3::new(position.x, 0.0, position.z).magnitude() - 3.0).abs() < 1e-4, "cuadro {}: {:?}", frame, position);
+            assert_eq!(position.y, 5.0);
+        }
+        let (_, half) = animation.frame(2, &camera, light);
+        assert!((half - Vec3::new(-3.0, 5.0, 0.0)).magnitude() < 1e-4);
+    }
+}
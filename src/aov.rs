@@ -0,0 +1,230 @@
+use nalgebra_glm::Vec3;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+use crate::hdr::HdrFramebuffer;
+use crate::light::Light;
+use crate::ray_intersect::SceneIntersect;
+use crate::config::Environment;
+use crate::shade_hit;
+
+// Pasadas extra que se pueden sacar además de la imagen final
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aov {
+    Depth,       // Distancia lineal desde la cámara (`Intersect::distance`)
+    Normal,      // Normal de la cara en coordenadas del mundo
+    Albedo,      // Color de la textura o del material, sin iluminar
+    MaterialId,  // Un color distinto por material
+    CubeIndex,   // Un color distinto por bloque: índice del cubo en la escena, o la celda en mundos por chunks
+    Shadow,      // Blanco donde el punto recibe la luz, negro donde está en sombra
+    Uv,          // Coordenadas de textura: u en rojo, v en verde
+    Tests,       // Cubos o celdas que probó el rayo, como mapa de calor (de azul a rojo)
+}
+
+impl Aov {
+    pub const ALL: [Aov; 8] = [Aov::Depth, Aov::Normal, Aov::Albedo, Aov::MaterialId, Aov::CubeIndex, Aov::Shadow, Aov::Uv, Aov::Tests];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Aov::Depth => "depth",
+            Aov::Normal => "normal",
+            Aov::Albedo => "albedo",
+            Aov::MaterialId => "material",
+            Aov::CubeIndex => "index",
+            Aov::Shadow => "shadow",
+            Aov::Uv => "uv",
+            Aov::Tests => "tests",
+        }
+    }
+
+    // Lista separada por comas (`depth,normal`) o `all`
+    pub fn parse_list(names: &str) -> Result<Vec<Aov>, String> {
+        if names == "all" {
+            return Ok(Aov::ALL.to_vec());
+        }
+        names.split(',').map(|name| {
+            Aov::ALL.into_iter().find(|aov| aov.name() == name.trim()).ok_or(format!(
+                "Pasada desconocida: {} (disponibles: depth, normal, albedo, material, index, shadow, uv, tests o all)", name
+            ))
+        }).collect()
+    }
+}
+
+// Lo que alcanzó el rayo primario de un píxel
+#[derive(Debug, Clone, Copy)]
+struct AovSample {
+    depth: f32,
+    normal: Vec3,
+    uv: [f32; 2],
+    albedo: Color,
+    material_id: u32,
+    cube_index: usize,
+    in_shadow: bool,
+}
+
+/// Pasadas de un render: un rayo por píxel, sin antialiasing ni profundidad de campo,
+/// para que los identificadores y las profundidades no se mezclen en los bordes.
+pub struct AovBuffers {
+    pub width: usize,
+    pub height: usize,
+    samples: Vec<Option<AovSample>>,  // None donde el rayo no alcanzó nada
+    tests: Vec<u32>,                  // Pruebas de intersección de cada rayo, haya alcanzado algo o no
+    view_distance: f32,               // Profundidad que se ve como negro
+}
+
+impl AovBuffers {
+    pub fn render<S: SceneIntersect + Sync + ?Sized>(objects: &S, camera: &Camera, light: &Light, environment: &Environment, width: usize, height: usize) -> Self {
+        let mut samples = vec![None; width * height];
+        let mut tests = vec![0; width * height];
+
+        samples.par_chunks_mut(width).zip(tests.par_chunks_mut(width)).enumerate().for_each(|(y, (row, row_tests))| {
+            for (x, (sample, tests)) in row.iter_mut().zip(row_tests).enumerate() {
+                let (ray_origin, ray_direction) = camera.primary_ray(x as f32, y as f32, width as f32, height as f32);
+                let intersect = objects.closest_intersect(&ray_origin, &ray_direction, environment.view_distance);
+                *tests = intersect.tests;
+                if !intersect.is_intersecting {
+                    continue;
+                }
+
+                let shading = shade_hit(intersect, &ray_origin, objects, light, environment);
+                *sample = Some(AovSample {
+                    depth: shading.distance,
+                    normal: shading.normal,
+                    uv: shading.uv,
+                    albedo: shading.base_color,
+                    material_id: shading.material.id(),
+                    cube_index: shading.object,
+                    in_shadow: shading.in_shadow,
+                });
+            }
+        });
+
+        AovBuffers { width, height, samples, tests, view_distance: environment.view_distance }
+    }
+
+    // Versión para ver en pantalla o guardar en 8 bits: profundidad en grises hasta la distancia de visión,
+    // normales de [-1, 1] a [0, 255] y un color distinto por material o cubo
+    pub fn to_framebuffer(&self, aov: Aov, framebuffer: &mut Framebuffer) {
+        // El mapa de calor se normaliza con el rayo más caro de la imagen
+        let max_tests = self.max_tests();
+
+        for y in 0..framebuffer.height.min(self.height) {
+            for x in 0..framebuffer.width.min(self.width) {
+                let index = y * self.width + x;
+                let color = match (aov, self.samples[index]) {
+                    (Aov::Tests, _) => heat(self.tests[index] as f32 / max_tests.max(1) as f32),
+                    (_, None) => Color::new(0, 0, 0),
+                    (Aov::Depth, Some(sample)) => {
+                        let gray = ((1.0 - sample.depth / self.view_distance) * 255.0).clamp(0.0, 255.0) as u8;
+                        Color::new(gray, gray, gray)
+                    }
+                    (Aov::Normal, Some(sample)) => {
+                        let n = (sample.normal * 0.5 + Vec3::new(0.5, 0.5, 0.5)) * 255.0;
+                        Color::new(n.x as u8, n.y as u8, n.z as u8)
+                    }
+                    (Aov::Albedo, Some(sample)) => sample.albedo,
+                    (Aov::MaterialId, Some(sample)) => false_color(sample.material_id),
+                    (Aov::CubeIndex, Some(sample)) => false_color(sample.cube_index as u32),
+                    (Aov::Shadow, Some(sample)) => if sample.in_shadow { Color::new(0, 0, 0) } else { Color::new(255, 255, 255) },
+                    (Aov::Uv, Some(sample)) => Color::new((sample.uv[0].clamp(0.0, 1.0) * 255.0) as u8, (sample.uv[1].clamp(0.0, 1.0) * 255.0) as u8, 0),
+                };
+                framebuffer.buffer[y * framebuffer.width + x] = color.to_hex();
+            }
+        }
+    }
+
+    // Mayor número de pruebas de un rayo en la imagen
+    pub fn max_tests(&self) -> u32 {
+        self.tests.iter().copied().max().unwrap_or(0)
+    }
+
+    // Valores crudos para `.exr` y `.hdr`: profundidad en unidades del mundo (infinita donde no hay nada),
+    // normales y UV sin cambiar, albedo lineal y las pruebas como números en los tres canales.
+    // Los materiales y cubos se numeran 1, 2, 3... en el orden en que aparecen en la imagen (0 es el fondo):
+    // los identificadores originales son hashes o índices enormes que un `f32` no guarda exactos
+    pub fn to_hdr(&self, aov: Aov) -> HdrFramebuffer {
+        let mut ids: HashMap<u64, u32> = HashMap::new();
+        let mut dense = |id: u64| {
+            let next = ids.len() as u32 + 1;
+            *ids.entry(id).or_insert(next) as f32
+        };
+
+        let mut framebuffer = HdrFramebuffer::new(self.width, self.height);
+        for ((pixel, sample), &tests) in framebuffer.buffer.iter_mut().zip(&self.samples).zip(&self.tests) {
+            *pixel = match (aov, sample) {
+                (Aov::Tests, _) => Vec3::new(1.0, 1.0, 1.0) * tests as f32,
+                (Aov::Depth, None) => Vec3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+                (_, None) => Vec3::zeros(),
+                (Aov::Depth, Some(s)) => Vec3::new(s.depth, s.depth, s.depth),
+                (Aov::Normal, Some(s)) => s.normal,
+                (Aov::Albedo, Some(s)) => s.albedo.to_linear(),
+                (Aov::MaterialId, Some(s)) => Vec3::new(1.0, 1.0, 1.0) * dense(s.material_id as u64),
+                (Aov::CubeIndex, Some(s)) => Vec3::new(1.0, 1.0, 1.0) * dense(s.cube_index as u64),
+                (Aov::Shadow, Some(s)) => if s.in_shadow { Vec3::zeros() } else { Vec3::new(1.0, 1.0, 1.0) },
+                (Aov::Uv, Some(s)) => Vec3::new(s.uv[0], s.uv[1], 0.0),
+            };
+        }
+        framebuffer
+    }
+
+    // Guarda una pasada; el formato sale de la extensión como en el render normal
+    pub fn save(&self, aov: Aov, path: &str) -> Result<(), String> {
+        let extension = path.rsplit_once('.').map(|(_, ext)| ext.to_lowercase()).unwrap_or_default();
+        if extension == "exr" {
+            return self.to_hdr(aov).save(path);
+        }
+        if extension == "hdr" {
+            // Radiance no guarda valores negativos: las normales van de [-1, 1] a [0, 1] como en 8 bits
+            let mut framebuffer = self.to_hdr(aov);
+            if aov == Aov::Normal {
+                for pixel in framebuffer.buffer.iter_mut() {
+                    *pixel = *pixel * 0.5 + Vec3::new(0.5, 0.5, 0.5);
+                }
+            }
+            return framebuffer.save(path);
+        }
+
+        let mut framebuffer = Framebuffer::new(self.width, self.height);
+        self.to_framebuffer(aov, &mut framebuffer);
+        framebuffer.save(path).map_err(|e| format!("No se pudo guardar {}: {}", path, e))
+    }
+}
+
+// Mapa de calor de 0 (azul) a 1 (rojo), pasando por verde y amarillo
+fn heat(t: f32) -> Color {
+    let channel = |center: f32| ((1.5 - (4.0 * t - center).abs()).clamp(0.0, 1.0) * 255.0) as u8;
+    Color::new(channel(3.0), channel(2.0), channel(1.0))
+}
+
+// Color saturado y estable para un identificador (los vecinos quedan con colores muy distintos)
+fn false_color(id: u32) -> Color {
+    let hash = id.wrapping_add(1).wrapping_mul(0x9E37_79B1);
+    let hash = hash ^ (hash >> 15);
+    Color::new((hash >> 16) as u8 | 0x40, (hash >> 8) as u8 | 0x40, hash as u8 | 0x40)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hdr_ids_are_numbered_in_order_of_appearance() {
+        let sample = |id: u32, index: usize| Some(AovSample {
+            depth: 1.0, normal: Vec3::new(0.0, 1.0, 0.0), uv: [0.0, 0.0], albedo: Color::new(0, 0, 0),
+            material_id: id, cube_index: index, in_shadow: false,
+        });
+        let buffers = AovBuffers {
+            width: 4,
+            height: 1,
+            samples: vec![sample(0xDEAD_BEEF, 70_000_001), None, sample(7, 3), sample(0xDEAD_BEEF, 70_000_001)],
+            tests: vec![0; 4],
+            view_distance: 20.0,
+        };
+
+        let ids = |aov| buffers.to_hdr(aov).buffer.iter().map(|pixel| pixel.x).collect::<Vec<_>>();
+        assert_eq!(ids(Aov::MaterialId), [1.0, 0.0, 2.0, 1.0]);
+        assert_eq!(ids(Aov::CubeIndex), [1.0, 0.0, 2.0, 1.0]);
+    }
+}
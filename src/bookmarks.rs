@@ -0,0 +1,113 @@
+use nalgebra_glm::Vec3;
+use std::fmt::Write as _;
+use std::fs;
+use crate::camera::Camera;
+
+pub const SLOTS: usize = 9;
+
+// Vista guardada de la cámara
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bookmark {
+    pub eye: Vec3,
+    pub center: Vec3,
+    pub up: Vec3,
+    pub fov: f32,  // En radianes
+}
+
+/// Vistas favoritas numeradas del 1 al 9. Se guardan como texto, una línea por vista ocupada:
+/// número, ojo (x y z), centro (x y z), up (x y z) y campo de visión en grados.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Bookmarks {
+    slots: [Option<Bookmark>; SLOTS],
+}
+
+impl Bookmarks {
+    // Guarda la vista actual en la posición `slot` (de 1 a 9)
+    pub fn store(&mut self, slot: usize, camera: &Camera) {
+        if let Some(entry) = self.slots.get_mut(slot.wrapping_sub(1)) {
+            *entry = Some(Bookmark { eye: camera.eye, center: camera.center, up: camera.up, fov: camera.fov });
+        }
+    }
+
+    // Lleva la cámara a la vista guardada; devuelve false si la posición está vacía
+    pub fn recall(&self, slot: usize, camera: &mut Camera) -> bool {
+        let Some(Some(bookmark)) = self.slots.get(slot.wrapping_sub(1)) else {
+            return false;
+        };
+
+        camera.eye = bookmark.eye;
+        camera.center = bookmark.center;
+        camera.up = bookmark.up;
+        camera.fov = bookmark.fov;
+        // El enfoque no se guarda: se vuelve a poner sobre el centro de la vista
+        camera.focus_distance = (bookmark.center - bookmark.eye).magnitude();
+        camera.target = None;
+        true
+    }
+
+    pub fn load(path: &str) -> Result<Bookmarks, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("No se pudo leer {}: {}", path, e))?;
+        let mut bookmarks = Bookmarks::default();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let error = |message: &str| format!("{}:{}: {}", path, number + 1, message);
+            let values: Vec<f32> = line.split_whitespace().map(|w| w.parse()).collect::<Result<_, _>>()
+                .map_err(|_| error("los valores deben ser números"))?;
+            if values.len() != 11 {
+                return Err(error("se esperaba número, ojo (x y z), centro (x y z), up (x y z) y campo de visión"));
+            }
+
+            let slot = values[0] as usize;
+            if values[0].fract() != 0.0 || !(1..=SLOTS).contains(&slot) {
+                return Err(error(&format!("el número de la vista debe estar entre 1 y {}", SLOTS)));
+            }
+            bookmarks.slots[slot - 1] = Some(Bookmark {
+                eye: Vec3::new(values[1], values[2], values[3]),
+                center: Vec3::new(values[4], values[5], values[6]),
+                up: Vec3::new(values[7], values[8], values[9]),
+                fov: values[10].to_radians(),
+            });
+        }
+
+        Ok(bookmarks)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let mut text = String::new();
+        for (index, bookmark) in self.slots.iter().enumerate() {
+            if let Some(b) = bookmark {
+                let _ = writeln!(
+                    text, "{} {} {} {} {} {} {} {} {} {} {}",
+                    index + 1, b.eye.x, b.eye.y, b.eye.z, b.center.x, b.center.y, b.center.z,
+                    b.up.x, b.up.y, b.up.z, b.fov.to_degrees()
+                );
+            }
+        }
+
+        fs::write(path, text).map_err(|e| format!("No se pudo guardar {}: {}", path, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recall_focuses_on_the_saved_center() {
+        let mut camera = Camera::new(Vec3::new(0.0, 0.0, 10.0), Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.store(1, &camera);
+
+        camera.eye = Vec3::new(0.0, 0.0, 3.0);
+        camera.focus_distance = 3.0;
+        assert!(bookmarks.recall(1, &mut camera));
+        assert_eq!(camera.eye, Vec3::new(0.0, 0.0, 10.0));
+        assert_eq!(camera.focus_distance, 10.0);
+        assert!(!bookmarks.recall(2, &mut camera));
+    }
+}
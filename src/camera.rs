@@ -1,10 +1,42 @@
 use nalgebra_glm::Vec3;
 use std::f32::consts::PI;
 
+// Modo de control de la cámara en el visor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CameraMode {
+    Orbit,  // Gira alrededor de `center`
+    Fly,    // Vuelo libre en primera persona
+}
+
+// Proyección con la que se generan los rayos primarios
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    Perspective,                     // Rayos desde el ojo, abiertos según `fov`
+    Orthographic { view_height: f32 },  // Rayos paralelos; `view_height` es la altura visible en unidades del mundo
+    Equirectangular,                 // Panorama de 360°: longitud en X y latitud en Y, con el centro de la imagen hacia adelante
+}
+
+// Punto `index` de `count` repartidos sobre el disco unitario (espiral de Vogel), girados `rotation` radianes.
+// Cubre la lente de forma pareja sin necesitar números aleatorios, así cada cuadro sale igual
+pub fn lens_sample(index: usize, count: usize, rotation: f32) -> (f32, f32) {
+    let golden_angle = PI * (3.0 - 5.0f32.sqrt());
+    let radius = ((index as f32 + 0.5) / count as f32).sqrt();
+    let angle = index as f32 * golden_angle + rotation;
+    (radius * angle.cos(), radius * angle.sin())
+}
+
+#[derive(Debug, Clone)]
 pub struct Camera {
     pub eye: Vec3,
     pub center: Vec3,
-    pub up: Vec3
+    pub up: Vec3,
+    pub fov: f32,                // Campo de visión vertical en radianes (solo en perspectiva)
+    pub projection: Projection,
+    pub aperture: f32,           // Radio de la lente; 0 es una cámara estenopeica (todo enfocado)
+    pub focus_distance: f32,     // Distancia del plano enfocado, medida sobre la dirección de la vista
+    pub min_radius: f32,         // Distancia mínima entre el ojo y el centro al acercarse
+    pub max_radius: f32,         // Distancia máxima al alejarse
+    pub target: Option<Vec3>,    // Centro hacia el que se está moviendo la cámara (encuadre suave)
 }
 
 impl Camera {
@@ -12,10 +44,103 @@ impl Camera {
         Camera {
             eye,
             center,
-            up
+            up,
+            fov: PI / 4.0,
+            projection: Projection::Perspective,
+            aperture: 0.0,
+            focus_distance: (center - eye).magnitude(),
+            min_radius: 1.0,
+            max_radius: 40.0,
+            target: None,
+        }
+    }
+
+    // Rayo primario (origen y dirección) que pasa por el píxel (x, y) de una imagen width x height
+    pub fn primary_ray(&self, x: f32, y: f32, width: f32, height: f32) -> (Vec3, Vec3) {
+        let aspect_ratio = width / height;
+
+        let screen_x = (2.0 * x) / width - 1.0;
+        let screen_y = -(2.0 * y) / height + 1.0;
+
+        match self.projection {
+            Projection::Perspective => {
+                let perspective_scale = (self.fov * 0.5).tan();
+                let screen_x = screen_x * aspect_ratio * perspective_scale;
+                let screen_y = screen_y * perspective_scale;
+
+                let ray_direction = nalgebra_glm::normalize(&Vec3::new(screen_x, screen_y, -1.0));
+                (self.eye, self.base_change(&ray_direction))
+            }
+            Projection::Orthographic { view_height } => {
+                // Todos los rayos miran hacia adelante; lo que cambia es su origen sobre el plano del ojo
+                let forward = (self.center - self.eye).normalize();
+                let right = forward.cross(&self.up).normalize();
+                let up = right.cross(&forward).normalize();
+
+                let half_height = view_height * 0.5;
+                let offset = right * (screen_x * aspect_ratio * half_height) + up * (screen_y * half_height);
+                (self.eye + offset, forward)
+            }
+            Projection::Equirectangular => {
+                let longitude = screen_x * PI;
+                let latitude = screen_y * PI * 0.5;
+                let ray_direction = Vec3::new(
+                    longitude.sin() * latitude.cos(),
+                    latitude.sin(),
+                    -longitude.cos() * latitude.cos(),
+                );
+                (self.eye, self.base_change(&ray_direction))
+            }
         }
     }
 
+    // Lo inverso de `primary_ray`: píxel (x, y) donde se ve `point`, o None si queda detrás de la cámara
+    // (o con la proyección equirectangular, donde no se usa)
+    pub fn project(&self, point: &Vec3, width: f32, height: f32) -> Option<(f32, f32)> {
+        let aspect_ratio = width / height;
+        let forward = (self.center - self.eye).normalize();
+        let right = forward.cross(&self.up).normalize();
+        let up = right.cross(&forward).normalize();
+
+        let offset = point - self.eye;
+        let depth = offset.dot(&forward);
+        let (screen_x, screen_y) = match self.projection {
+            Projection::Perspective if depth > 1e-2 => {
+                let perspective_scale = (self.fov * 0.5).tan() * depth;
+                (offset.dot(&right) / (aspect_ratio * perspective_scale), offset.dot(&up) / perspective_scale)
+            }
+            Projection::Orthographic { view_height } if depth > 0.0 => {
+                let half_height = view_height * 0.5;
+                (offset.dot(&right) / (aspect_ratio * half_height), offset.dot(&up) / half_height)
+            }
+            _ => return None,
+        };
+
+        Some(((screen_x + 1.0) * width * 0.5, (1.0 - screen_y) * height * 0.5))
+    }
+
+    // Lente delgada: mueve el origen del rayo a un punto de la lente (`sample` dentro del disco unitario)
+    // y lo redirige hacia donde el rayo original corta el plano enfocado
+    pub fn lens_ray(&self, ray_origin: Vec3, ray_direction: Vec3, sample: (f32, f32)) -> (Vec3, Vec3) {
+        if self.aperture <= 0.0 || self.projection == Projection::Equirectangular {
+            return (ray_origin, ray_direction);
+        }
+
+        let forward = (self.center - self.eye).normalize();
+        let right = forward.cross(&self.up).normalize();
+        let up = right.cross(&forward).normalize();
+
+        let focal_point = ray_origin + ray_direction * (self.focus_distance / ray_direction.dot(&forward));
+        let lens_origin = ray_origin + (right * sample.0 + up * sample.1) * self.aperture;
+        (lens_origin, (focal_point - lens_origin).normalize())
+    }
+
+    // Enfoca el plano que pasa por `point`
+    pub fn focus_on(&mut self, point: Vec3) {
+        let forward = (self.center - self.eye).normalize();
+        self.focus_distance = (point - self.eye).dot(&forward).max(0.1);
+    }
+
     pub fn base_change(&self, vector: &Vec3) -> Vec3 {
         let forward = (self.center - self.eye).normalize();
         let right = forward.cross(&self.up).normalize();
@@ -23,7 +148,7 @@ impl Camera {
 
         let rotated = vector.x * right + vector.y * up - vector.z * forward;
 
-        return rotated.normalize();
+        rotated.normalize()
     }
 
     pub fn orbit(&mut self, delta_yaw: f32, delta_pitch: f32) {
@@ -45,4 +170,158 @@ impl Camera {
 
         self.eye = new_eye;
     }
-}
\ No newline at end of file
+
+    // Vuelo libre: mueve el ojo y el centro juntos, hacia adelante (donde mira la cámara),
+    // a la derecha y hacia arriba en el eje Y del mundo
+    pub fn fly(&mut self, forward: f32, right: f32, up: f32) {
+        let direction = (self.center - self.eye).normalize();
+        let right_vector = direction.cross(&self.up).normalize();
+
+        let offset = direction * forward + right_vector * right + Vec3::new(0.0, up, 0.0);
+        self.eye += offset;
+        self.center += offset;
+    }
+
+    // Mirar alrededor en primera persona: gira el centro alrededor del ojo
+    pub fn look(&mut self, delta_yaw: f32, delta_pitch: f32) {
+        let direction = self.center - self.eye;
+        let distance = direction.magnitude();
+
+        let current_yaw = direction.z.atan2(direction.x);
+        let distance_xz = (direction.x * direction.x + direction.z * direction.z).sqrt();
+        let current_pitch = direction.y.atan2(distance_xz);
+
+        let new_yaw = (current_yaw + delta_yaw) % (2.0 * PI);
+        let new_pitch = (current_pitch + delta_pitch).clamp(-PI / 2.0 + 0.1, PI / 2.0 - 0.1);
+
+        self.center = self.eye + Vec3::new(
+            distance * new_yaw.cos() * new_pitch.cos(),
+            distance * new_pitch.sin(),
+            distance * new_yaw.sin() * new_pitch.cos()
+        );
+    }
+
+    // Acercar o alejar el ojo del centro sin salirse de los límites
+    pub fn dolly(&mut self, delta: f32) {
+        let radius_vector = self.eye - self.center;
+        let radius = (radius_vector.magnitude() + delta).clamp(self.min_radius, self.max_radius);
+        self.eye = self.center + radius_vector.normalize() * radius;
+    }
+
+    // Desplazar ojo y centro juntos sobre el plano de la vista
+    pub fn pan(&mut self, right: f32, up: f32) {
+        let forward = (self.center - self.eye).normalize();
+        let right_vector = forward.cross(&self.up).normalize();
+        let up_vector = right_vector.cross(&forward).normalize();
+
+        let offset = right_vector * right + up_vector * up;
+        self.eye += offset;
+        self.center += offset;
+    }
+
+    // Empezar a mover el centro hacia un punto (por ejemplo el centro de un cubo)
+    pub fn frame(&mut self, point: Vec3) {
+        self.target = Some(point);
+    }
+
+    // Avanza el encuadre suave: el centro se acerca al objetivo y el ojo lo acompaña
+    pub fn update(&mut self, delta_time: f32) {
+        let Some(target) = self.target else {
+            return;
+        };
+
+        let remaining = target - self.center;
+        let offset = if remaining.magnitude() < 1e-3 {
+            self.target = None;
+            remaining
+        } else {
+            remaining * (1.0 - (-8.0 * delta_time).exp())
+        };
+
+        self.eye += offset;
+        self.center += offset;
+    }
+
+    // Acercar o alejar: en perspectiva mueve el ojo, en ortográfica cambia la altura visible
+    pub fn zoom(&mut self, factor: f32) {
+        match &mut self.projection {
+            Projection::Perspective | Projection::Equirectangular => {
+                let radius = (self.eye - self.center).magnitude();
+                self.dolly(radius * (factor - 1.0));
+            }
+            Projection::Orthographic { view_height } => {
+                *view_height = (*view_height * factor).clamp(self.min_radius, self.max_radius);
+            }
+        }
+    }
+
+    // Cambia entre perspectiva y ortográfica; la altura visible se elige para que el centro
+    // se vea del mismo tamaño en las dos proyecciones
+    pub fn toggle_projection(&mut self) {
+        self.projection = match self.projection {
+            Projection::Perspective => {
+                let radius = (self.eye - self.center).magnitude();
+                Projection::Orthographic { view_height: 2.0 * radius * (self.fov * 0.5).tan() }
+            }
+            Projection::Orthographic { .. } | Projection::Equirectangular => Projection::Perspective,
+        };
+    }
+
+    // Altura visible en ortográfica; en las otras proyecciones no cambia nada
+    pub fn set_view_height(&mut self, height: f32) {
+        if let Projection::Orthographic { view_height } = &mut self.projection {
+            *view_height = height;
+        }
+    }
+
+    // Cámara nivelada con el horizonte (up = +Y) que mira en la misma dirección horizontal,
+    // para que los panoramas no salgan inclinados
+    pub fn leveled(&self) -> Camera {
+        let direction = self.center - self.eye;
+        let horizontal = Vec3::new(direction.x, 0.0, direction.z);
+        let horizontal = if horizontal.magnitude() > 1e-6 { horizontal.normalize() } else { Vec3::new(0.0, 0.0, 1.0) };
+
+        let mut camera = Camera::new(self.eye, self.eye + horizontal, Vec3::new(0.0, 1.0, 0.0));
+        camera.projection = self.projection;
+        camera.fov = self.fov;
+        camera
+    }
+
+    // Las seis cámaras de 90° de un cubemap desde el ojo, con el sufijo de cada cara (+X, -X, +Y, -Y, +Z, -Z)
+    pub fn cubemap_faces(&self) -> Vec<(&'static str, Camera)> {
+        let faces = [
+            ("px", Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0)),
+            ("nx", Vec3::new(-1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0)),
+            ("py", Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, -1.0)),
+            ("ny", Vec3::new(0.0, -1.0, 0.0), Vec3::new(0.0, 0.0, 1.0)),
+            ("pz", Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 1.0, 0.0)),
+            ("nz", Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 1.0, 0.0)),
+        ];
+
+        faces.into_iter().map(|(name, direction, up)| {
+            let mut camera = Camera::new(self.eye, self.eye + direction, up);
+            camera.fov = PI / 2.0;
+            (name, camera)
+        }).collect()
+    }
+
+    // Vista isométrica verdadera: ortográfica, a 45° en horizontal y arctan(1/√2) (≈35.26°) de inclinación.
+    // Se conserva el centro, la distancia y el cuadrante desde el que se estaba mirando
+    pub fn isometric(&mut self) {
+        if self.projection == Projection::Perspective {
+            self.toggle_projection();
+        }
+
+        let radius_vector = self.eye - self.center;
+        let radius = radius_vector.magnitude();
+        let current_yaw = radius_vector.z.atan2(radius_vector.x);
+        let yaw = ((current_yaw - PI / 4.0) / (PI / 2.0)).round() * (PI / 2.0) + PI / 4.0;
+        let pitch = (1.0 / 2.0f32.sqrt()).atan();
+        self.up = Vec3::new(0.0, 1.0, 0.0);
+        self.eye = self.center + Vec3::new(
+            radius * yaw.cos() * pitch.cos(),
+            radius * pitch.sin(),
+            radius * yaw.sin() * pitch.cos()
+        );
+    }
+}
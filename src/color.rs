@@ -30,8 +30,17 @@ impl Color {
         Color { r, g, b }
     }
 
+    // Color en RGB lineal (0..1), deshaciendo la curva sRGB de las texturas y colores de 8 bits
+    pub fn to_linear(self) -> nalgebra_glm::Vec3 {
+        let decode = |c: u8| {
+            let c = c as f32 / 255.0;
+            if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+        };
+        nalgebra_glm::Vec3::new(decode(self.r), decode(self.g), decode(self.b))
+    }
+
     // Function to return the color as a hex value
-    pub fn to_hex(&self) -> u32 {
+    pub fn to_hex(self) -> u32 {
         ((self.r as u32) << 16) | ((self.g as u32) << 8) | (self.b as u32)
     }
 }
@@ -0,0 +1,193 @@
+use nalgebra_glm::Vec3;
+use std::fs;
+use crate::color::Color;
+use crate::upscale::UpscaleFilter;
+
+// Lo que el renderizador necesita saber de la escena además de los cubos y la luz
+#[derive(Debug, Clone, Copy)]
+pub struct Environment {
+    pub background: Color,   // Color cuando el rayo no alcanza nada
+    pub ambient: f32,        // Luz ambiente, también en las zonas de sombra
+    pub view_distance: f32,  // Distancia máxima a la que se buscan intersecciones
+    pub time: f32,           // Segundos desde el inicio de la animación (desplaza las texturas animadas)
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Environment {
+            background: Color::new(64, 128, 96),
+            ambient: 0.5,
+            view_distance: 20.0,
+            time: 0.0,
+        }
+    }
+}
+
+/// Ajustes del visor y de los renders. Se parte de los valores por defecto, después se aplica el archivo
+/// de configuración (si hay) y al final las opciones de la línea de comandos con el mismo nombre.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub window_width: usize,
+    pub window_height: usize,
+    pub scale: f32,                    // Escala inicial del framebuffer respecto a la ventana
+    pub min_scale: f32,                // Límites de la resolución dinámica
+    pub max_scale: f32,
+    pub target_fps: f32,
+    pub upscale: UpscaleFilter,
+    pub sharpen: f32,
+    pub intensity: f32,                // Intensidad inicial de la luz
+    pub light_position: Option<Vec3>,  // None: la que trae la escena
+    pub view_height: Option<f32>,      // Altura visible en ortográfica; None: la que deja el centro del mismo tamaño
+    pub music: String,
+    pub environment: Environment,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            window_width: 800,
+            window_height: 600,
+            scale: 0.75,
+            min_scale: 0.25,
+            max_scale: 1.0,
+            target_fps: 30.0,
+            upscale: UpscaleFilter::Nearest,
+            sharpen: 0.0,
+            intensity: 20.0,
+            light_position: None,
+            view_height: None,
+            music: "assets/music.wav".to_string(),
+            environment: Environment::default(),
+        }
+    }
+}
+
+impl Config {
+    // Nombres de los ajustes; en la línea de comandos van con `--` delante
+    pub const KEYS: [&'static str; 15] = [
+        "window-width", "window-height", "scale", "min-scale", "max-scale", "target-fps", "upscale", "sharpen",
+        "intensity", "light", "view-height", "music", "background", "ambient", "view-distance",
+    ];
+
+    // Ajustes que solo usa el visor; los subcomandos que renderizan a un archivo no los aceptan
+    pub const VIEWER_KEYS: [&'static str; 9] = [
+        "window-width", "window-height", "scale", "min-scale", "max-scale", "target-fps", "upscale", "sharpen", "music",
+    ];
+
+    // Lee un archivo con una línea `clave = valor` por ajuste (ver `parse`)
+    pub fn load(path: &str) -> Result<(Config, Vec<String>), String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("No se pudo leer {}: {}", path, e))?;
+        Config::parse(&text).map_err(|e| format!("{}:{}", path, e))
+    }
+
+    // Aplica sobre los valores por defecto un ajuste `clave = valor` por línea; las líneas vacías y las
+    // que empiezan con `#` se ignoran. Devuelve también las claves que aparecen, en orden
+    pub fn parse(text: &str) -> Result<(Config, Vec<String>), String> {
+        let mut config = Config::default();
+        let mut keys = Vec::new();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line.split_once('=')
+                .ok_or(format!("{}: se esperaba `clave = valor`", number + 1))?;
+            config.set(key.trim(), value.trim()).map_err(|e| format!("{}: {}", number + 1, e))?;
+            keys.push(key.trim().to_string());
+        }
+
+        Ok((config, keys))
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let number = |value: &str| value.parse::<f32>().map_err(|_| format!("Valor inválido para {}: {}", key, value));
+        let size = |value: &str| value.parse::<usize>().ok().filter(|&v| v > 0)
+            .ok_or(format!("Valor inválido para {}: {}", key, value));
+
+        match key {
+            "window-width" => self.window_width = size(value)?,
+            "window-height" => self.window_height = size(value)?,
+            "scale" => self.scale = number(value)?,
+            "min-scale" => self.min_scale = number(value)?,
+            "max-scale" => self.max_scale = number(value)?,
+            "target-fps" => self.target_fps = number(value)?,
+            "upscale" => self.upscale = UpscaleFilter::parse(value)?,
+            "sharpen" => self.sharpen = number(value)?,
+            "intensity" => self.intensity = number(value)?,
+            "light" => {
+                let [x, y, z] = triple(value).ok_or(format!("Valor inválido para light: {} (usa X,Y,Z)", value))?;
+                self.light_position = Some(Vec3::new(x, y, z));
+            }
+            "view-height" => {
+                let height = number(value)?;
+                if height <= 0.0 {
+                    return Err(format!("Valor inválido para view-height: {}", value));
+                }
+                self.view_height = Some(height);
+            }
+            "music" => self.music = value.to_string(),
+            "background" => {
+                let [r, g, b] = triple(value).filter(|c| c.iter().all(|&v| (0.0..=255.0).contains(&v)))
+                    .ok_or(format!("Valor inválido para background: {} (usa R,G,B de 0 a 255)", value))?;
+                self.environment.background = Color::new(r as u8, g as u8, b as u8);
+            }
+            "ambient" => self.environment.ambient = number(value)?,
+            "view-distance" => self.environment.view_distance = number(value)?,
+            _ => return Err(format!("Ajuste desconocido: {}", key)),
+        }
+        Ok(())
+    }
+}
+
+// Tres números separados por comas
+fn triple(value: &str) -> Option<[f32; 3]> {
+    let parts: Vec<f32> = value.split(',').map(|v| v.trim().parse()).collect::<Result<_, _>>().ok()?;
+    parts.try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comments_and_blank_lines_are_skipped() {
+        let text = "# Comentario\n\n   \n  # Otro comentario con = adentro\nintensity = 12.5\n\tambient=0.25  \n";
+        let (config, keys) = Config::parse(text).unwrap();
+
+        assert_eq!(keys, ["intensity", "ambient"]);
+        assert_eq!(config.intensity, 12.5);
+        assert_eq!(config.environment.ambient, 0.25);
+        // Lo que no aparece queda con el valor por defecto
+        assert_eq!(config.window_width, Config::default().window_width);
+    }
+
+    #[test]
+    fn colors_and_positions() {
+        let (config, _) = Config::parse("background = 0, 10,255\nlight = 1.5,-2, 3").unwrap();
+        assert_eq!(config.environment.background, Color::new(0, 10, 255));
+        assert_eq!(config.light_position, Some(Vec3::new(1.5, -2.0, 3.0)));
+    }
+
+    #[test]
+    fn bad_colors_are_rejected() {
+        for value in ["1,2", "1,2,3,4", "0,0,256", "-1,0,0", "rojo", "1,,2"] {
+            let error = Config::parse(&format!("background = {}", value)).unwrap_err();
+            assert!(error.starts_with("1: Valor inválido para background"), "{}: {}", value, error);
+        }
+    }
+
+    #[test]
+    fn view_height_must_be_positive() {
+        assert_eq!(Config::default().view_height, None);
+        assert_eq!(Config::parse("view-height = 4.5").unwrap().0.view_height, Some(4.5));
+        assert_eq!(Config::parse("view-height = 0").unwrap_err(), "1: Valor inválido para view-height: 0");
+    }
+
+    #[test]
+    fn unknown_keys_and_malformed_lines_report_the_line() {
+        assert_eq!(Config::parse("intensity = 3\nbrillo = 2").unwrap_err(), "2: Ajuste desconocido: brillo");
+        assert_eq!(Config::parse("\nintensity 3").unwrap_err(), "2: se esperaba `clave = valor`");
+        assert_eq!(Config::parse("window-width = 0").unwrap_err(), "1: Valor inválido para window-width: 0");
+    }
+}
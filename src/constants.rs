@@ -1,2 +1,2 @@
-pub const FRAMEBUFFER_SCALE_FACTOR: f32 = 0.75; 
-pub const INTENSITY: f32 = 20.0;
\ No newline at end of file
+pub const BLOCK_SIZE: f32 = 0.5;
+pub const DOF_SAMPLES: usize = 16;  // Rayos por píxel cuando la lente tiene apertura
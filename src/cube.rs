@@ -1,12 +1,14 @@
 use nalgebra_glm::Vec3;
-use crate::ray_intersect::{RayIntersect, Intersect};
+use crate::ray_intersect::{edge_normal, face_distance, RayIntersect, Intersect, SceneIntersect, EDGE_EPSILON};
 use crate::material::Material;
+use crate::texture::calculate_uv;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Cube {
     pub min: Vec3, // Coordenada mínima (esquina inferior del cubo)
     pub max: Vec3, // Coordenada máxima (esquina superior del cubo)
     pub material: Material,
+    pub uv_tile: Option<f32>, // Tamaño de bloque con el que se repite la textura (None = la textura cubre toda la cara)
 }
 
 impl RayIntersect for Cube {
@@ -30,21 +32,24 @@ impl RayIntersect for Cube {
         let tmin = t1.max(t3).max(t5);
         let tmax = t2.min(t4).min(t6);
 
-        // Verificamos si hay una intersección válida
-        if tmax >= tmin && tmin > 0.0 {
+        // Verificamos si hay una intersección válida. Un rayo que solo roza una arista no cuenta,
+        // igual que en el recorrido por celdas de `World`
+        if tmax - tmin >= EDGE_EPSILON && tmin > 0.0 {
             let distance = tmin;
             let point = ray_origin + ray_direction * distance;
 
-            // Calculamos la normal del cubo
+            // Calculamos la normal del cubo: apunta en contra del rayo en el eje por el que entra
+            // (tras el intercambio t1/t3/t5 son siempre los planos de entrada)
             let mut normal = Vec3::zeros();
-            if tmin == t1 || tmin == t2 {
-                normal.x = if tmin == t1 { -1.0 } else { 1.0 };
-            } else if tmin == t3 || tmin == t4 {
-                normal.y = if tmin == t3 { -1.0 } else { 1.0 };
-            } else if tmin == t5 || tmin == t6 {
-                normal.z = if tmin == t5 { -1.0 } else { 1.0 };
+            if tmin == t1 {
+                normal.x = -ray_direction.x.signum();
+            } else if tmin == t3 {
+                normal.y = -ray_direction.y.signum();
+            } else {
+                normal.z = -ray_direction.z.signum();
             }
-            return Intersect::new(point, normal, distance, self.material.clone());
+            let uv = calculate_uv(point, normal, self.min, self.max, self.uv_tile);
+            return Intersect::new(point, normal, distance, uv, self.material.clone());
         }
 
         Intersect::empty()
@@ -57,5 +62,136 @@ impl Cube {
         // El centro es el punto medio entre las coordenadas mínimas y máximas
         (self.min + self.max) * 0.5
     }
+
+    // Distancia desde un punto hasta la superficie más cercana del cubo (0 si está dentro)
+    pub fn distance_to(&self, point: &Vec3) -> f32 {
+        let dx = (self.min.x - point.x).max(point.x - self.max.x).max(0.0);
+        let dy = (self.min.y - point.y).max(point.y - self.max.y).max(0.0);
+        let dz = (self.min.z - point.z).max(point.z - self.max.z).max(0.0);
+        (dx * dx + dy * dy + dz * dz).sqrt()
+    }
+}
+
+impl SceneIntersect for [Cube] {
+    fn closest_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3, max_distance: f32) -> Intersect {
+        let mut intersect = Intersect::empty();
+        let mut zbuffer = f32::INFINITY;
+        let mut tests = 0;
+
+        for (index, object) in self.iter().enumerate() {
+            // Verificamos si el objeto está cerca de la cámara antes de calcular intersecciones
+            if object.distance_to(ray_origin) > max_distance {  // Omitimos objetos que están demasiado lejos
+                continue;
+            }
+
+            tests += 1;
+            let tmp = object.ray_intersect(ray_origin, ray_direction);
+            if tmp.is_intersecting && tmp.distance < zbuffer && tmp.distance <= max_distance {
+                zbuffer = tmp.distance;
+                intersect = Intersect { object: index, ..tmp };
+            }
+        }
+
+        // Si el rayo cae sobre una arista del cubo, la cara y el cubo los decide `edge_normal`
+        if intersect.is_intersecting {
+            let cube = &self[intersect.object];
+            let point = intersect.point;
+            // Una caja fusionada sigue teniendo aristas entre los bloques que la forman
+            let on_grid = |axis: usize| cube.uv_tile.is_some_and(|tile| {
+                let t = (point[axis] - cube.min[axis]) / tile;
+                (t - t.round()).abs() < EDGE_EPSILON / tile
+            });
+            let axes: Vec<usize> = (0..3).filter(|&axis| intersect.normal[axis] != 0.0 || (ray_direction[axis] != 0.0 && (
+                (point[axis] - cube.max[axis]).abs() < EDGE_EPSILON || (point[axis] - cube.min[axis]).abs() < EDGE_EPSILON || on_grid(axis)
+            ))).collect();
+            if axes.len() > 1 {
+                let inside = |q: &Vec3| self.iter().position(|c| (0..3).all(|axis| c.min[axis] <= q[axis] && q[axis] <= c.max[axis]));
+                let (normal, owner) = edge_normal(&point, ray_direction, &axes, |q| inside(q).is_some());
+                let index = inside(&owner).unwrap_or(intersect.object);
+                let cube = &self[index];
+                let distance = face_distance(ray_origin, ray_direction, &normal, &cube.min, &cube.max);
+                let point = ray_origin + ray_direction * distance;
+                intersect = Intersect {
+                    object: index,
+                    ..Intersect::new(point, normal, distance, calculate_uv(point, normal, cube.min, cube.max, cube.uv_tile), cube.material.clone())
+                };
+            }
+
+            // Un rayo contenido en el plano entre dos cubos los toca a la vez: se queda con el del
+            // lado positivo, igual que `World`
+            let (cube, point) = (&self[intersect.object], intersect.point);
+            for axis in (0..3).filter(|&axis| ray_direction[axis] == 0.0 && (point[axis] - cube.max[axis]).abs() < EDGE_EPSILON) {
+                let mut probe = point - intersect.normal * 1e-3;
+                probe[axis] += 1e-3;
+                let twin = self.iter().position(|c| (0..3).all(|i| c.min[i] <= probe[i] && probe[i] <= c.max[i]));
+                if let Some(index) = twin {
+                    let cube = &self[index];
+                    intersect = Intersect {
+                        object: index,
+                        uv: calculate_uv(point, intersect.normal, cube.min, cube.max, cube.uv_tile),
+                        material: cube.material.clone(),
+                        ..intersect
+                    };
+                }
+            }
+        }
+
+        Intersect { tests, ..intersect }
+    }
+
+    fn occluded(&self, ray_origin: &Vec3, ray_direction: &Vec3, max_distance: f32) -> bool {
+        self.iter().any(|object| {
+            let intersect = object.ray_intersect(ray_origin, ray_direction);
+            intersect.is_intersecting && intersect.distance < max_distance
+        })
+    }
+
+    fn pick(&self, ray_origin: &Vec3, ray_direction: &Vec3, max_distance: f32) -> Option<Cube> {
+        let mut picked = None;
+        let mut zbuffer = max_distance;
+
+        for object in self {
+            let intersect = object.ray_intersect(ray_origin, ray_direction);
+            if intersect.is_intersecting && intersect.distance <= zbuffer {
+                zbuffer = intersect.distance;
+                picked = Some(object);
+            }
+        }
+
+        picked.cloned()
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_cube() -> Cube {
+        Cube { min: Vec3::new(0.0, 0.0, 0.0), max: Vec3::new(1.0, 1.0, 1.0), material: Material::default(), uv_tile: None }
+    }
+
+    #[test]
+    fn normals_point_out_of_the_cube() {
+        let cube = unit_cube();
+        // Un rayo por cada cara, lanzado desde fuera hacia el centro
+        let axes = [
+            Vec3::new(1.0, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 0.0, -1.0),
+        ];
+        for direction in axes {
+            let origin = cube.center() - direction * 3.0;
+            let hit = cube.ray_intersect(&origin, &direction);
+            assert!(hit.is_intersecting, "sin impacto en {:?}", direction);
+            assert_eq!(hit.normal, -direction, "normal hacia dentro al entrar por {:?}", direction);
+        }
+    }
+
+    #[test]
+    fn distance_to_measures_to_the_nearest_face() {
+        let floor = Cube { min: Vec3::new(-50.0, -1.0, -50.0), max: Vec3::new(50.0, 0.0, 50.0), material: Material::default(), uv_tile: None };
+        // Lejos de la esquina mínima pero justo encima de la superficie
+        assert_eq!(floor.distance_to(&Vec3::new(0.0, 2.0, 0.0)), 2.0);
+        assert_eq!(floor.distance_to(&Vec3::new(0.0, -0.5, 0.0)), 0.0);
+    }
+}
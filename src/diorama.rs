@@ -1,11 +1,12 @@
 use nalgebra_glm::Vec3;
 use crate::cube::Cube;
 use crate::material::Material;
+use crate::constants::BLOCK_SIZE;
 
 pub fn generate_diorama() -> Vec<Cube> {
     let mut objects = Vec::new();
 
-    let cube_size = 0.5;
+    let cube_size = BLOCK_SIZE;
 
     //Cubos de diamantes de primero
     for x in (0..(1.0 / cube_size) as usize).map(|i| i as f32 * cube_size) {
@@ -14,6 +15,7 @@ pub fn generate_diorama() -> Vec<Cube> {
                 min: Vec3::new(x, 0.0, z),  // Coordenada mínima del cubo
                 max: Vec3::new(x + cube_size, cube_size, z + cube_size),  // Coordenada máxima del cubo
                 material: Material::diamond(),  // Usamos referencia al material
+                uv_tile: None,
             });
         }
     }
@@ -27,6 +29,7 @@ pub fn generate_diorama() -> Vec<Cube> {
                 min: Vec3::new(x, 0.0, z),  // Coordenada mínima del cubo
                 max: Vec3::new(x + cube_size, cube_size, z + cube_size),  // Coordenada máxima (cubos 0.5x0.5)
                 material: Material::stone(),  // Material
+                uv_tile: None,
             });
             z += cube_size;  // Incremento manual para el eje Z
         }
@@ -35,11 +38,12 @@ pub fn generate_diorama() -> Vec<Cube> {
     
     //Linea de cubos
     for x in (0..(1.0 / cube_size) as usize).map(|i| i as f32 * cube_size) {
-        for z in (0..(4.0 / cube_size) as usize).map(|i| i as f32 * cube_size) {
+        for z in (2..(4.0 / cube_size) as usize).map(|i| i as f32 * cube_size) {
             objects.push(Cube {
                 min: Vec3::new(x, 0.0, z),  // Coordenada mínima del cubo
                 max: Vec3::new(x + cube_size, cube_size, z + cube_size),  // Coordenada máxima del cubo
                 material: Material::stone(),  // Usamos referencia al material
+                uv_tile: None,
             });
         }
     }
@@ -51,6 +55,7 @@ pub fn generate_diorama() -> Vec<Cube> {
                 min: Vec3::new(4.0, y, z),  // Coordenada mínima del cubo
                 max: Vec3::new(4.0 + cube_size, y + cube_size, z + cube_size),  // Coordenada máxima del cubo
                 material: Material::stone(),  // Usamos referencia al material
+                uv_tile: None,
             });
         }
     }
@@ -60,8 +65,9 @@ pub fn generate_diorama() -> Vec<Cube> {
         for x in (0..(5.0 / cube_size) as usize).map(|i| i as f32 * cube_size) {
             objects.push(Cube {
                 min: Vec3::new(x, y, 4.0),  // Coordenada mínima del cubo
-                max: Vec3::new(cube_size, y + cube_size, 4.0 + cube_size),  // Coordenada máxima del cubo
+                max: Vec3::new(x + cube_size, y + cube_size, 4.0 + cube_size),  // Coordenada máxima del cubo
                 material: Material::stone(),  // Usamos referencia al material
+                uv_tile: None,
             });
         }
     }
@@ -73,28 +79,31 @@ pub fn generate_diorama() -> Vec<Cube> {
                 min: Vec3::new(x, 4.0, z),  // Coordenada mínima del cubo
                 max: Vec3::new(x + cube_size, 4.0 + cube_size, z + cube_size),  // Coordenada máxima del cubo
                 material: Material::grass(),  // Usamos referencia al material
+                uv_tile: None,
             });
         }
     }
 
     //Techo tierra
     for x in (5..(5.0 / cube_size) as usize).map(|i| i as f32 * cube_size) {
-        for z in (2..(5.0 / cube_size) as usize).map(|i| i as f32 * cube_size) {
+        for z in (4..(5.0 / cube_size) as usize).map(|i| i as f32 * cube_size) {
             objects.push(Cube {
                 min: Vec3::new(x, 4.0, z),  // Coordenada mínima del cubo
                 max: Vec3::new(x + cube_size, 4.0 + cube_size, z + cube_size),  // Coordenada máxima del cubo
                 material: Material::dirt(),  // Usamos referencia al material
+                uv_tile: None,
             });
         }
     }
 
     //Techo arena 
-    for x in (0..(3.0 / cube_size) as usize).map(|i| i as f32 * cube_size) {
+    for x in (0..(2.5 / cube_size) as usize).map(|i| i as f32 * cube_size) {
         for z in (4..(5.0 / cube_size) as usize).map(|i| i as f32 * cube_size) {
             objects.push(Cube {
                 min: Vec3::new(x, 4.0, z),  // Coordenada mínima del cubo
                 max: Vec3::new(x + cube_size, 4.0 + cube_size, z + cube_size),  // Coordenada máxima del cubo
                 material: Material::sand(),  // Usamos referencia al material
+                uv_tile: None,
             });
         }
     }
@@ -105,18 +114,21 @@ pub fn generate_diorama() -> Vec<Cube> {
         min: Vec3::new(3.5, 3.5, 3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.5 + cube_size, 3.5 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::azalea(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(3.0, 3.5, 3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.0 + cube_size, 3.5 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::azalea(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(2.5, 3.5, 3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(2.5 + cube_size, 3.5 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::azalea(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
     
     //Azalea fila 2
@@ -124,18 +136,21 @@ pub fn generate_diorama() -> Vec<Cube> {
         min: Vec3::new(3.5, 3.0, 3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.5 + cube_size, 3.0 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::azalea(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(3.0, 3.0, 3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.0 + cube_size, 3.0 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::azalea(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(2.5, 3.0, 3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(2.5 + cube_size, 3.0 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::azalea(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     //Azalea fila 3
@@ -143,36 +158,35 @@ pub fn generate_diorama() -> Vec<Cube> {
         min: Vec3::new(3.5, 2.5, 3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.5 + cube_size, 2.5 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::azalea(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(3.0, 2.5, 3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.0 + cube_size, 2.5 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::azalea(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(3.5, 2.0, 3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.5 + cube_size, 2.0 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::azalea(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(3.0, 2.0, 3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.0 + cube_size, 2.0 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::azalea(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(3.5, 1.5, 3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.5 + cube_size, 1.5 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::azalea(),  // Material del cubo (o cualquier otro material que prefieras)
-    });
-
-    objects.push(Cube {
-        min: Vec3::new(3.0, 2.5, 3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
-        max: Vec3::new(3.0 + cube_size, 2.5 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
-        material: Material::azalea(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     //Bamboo
@@ -181,24 +195,28 @@ pub fn generate_diorama() -> Vec<Cube> {
         min: Vec3::new(3.5, 3.5, 0.0),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.5 + cube_size, 3.5 + cube_size, cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::bamboo(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(3.5, 3.5, 0.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.5 + cube_size, 3.5 + cube_size, 0.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::bamboo(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(3.5, 3.5, 1.0),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.5 + cube_size, 3.5 + cube_size, 1.0 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::bamboo(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(3.5, 3.5, 1.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.5 + cube_size, 3.5 + cube_size, 1.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::bamboo(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     //Bamboo fila 2
@@ -206,18 +224,21 @@ pub fn generate_diorama() -> Vec<Cube> {
         min: Vec3::new(3.5, 3.0, 0.0),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.5 + cube_size, 3.0 + cube_size, cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::bamboo(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(3.5, 3.0, 0.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.5 + cube_size, 3.0 + cube_size, 0.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::bamboo(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(3.5, 3.0, 1.0),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.5 + cube_size, 3.0 + cube_size, 1.0 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::bamboo(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
     
 
@@ -226,12 +247,14 @@ pub fn generate_diorama() -> Vec<Cube> {
         min: Vec3::new(3.5, 2.5, 0.0),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.5 + cube_size, 2.5 + cube_size, cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::bamboo(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(3.5, 2.5, 0.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.5 + cube_size, 2.5 + cube_size, 0.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::bamboo(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     //Bamboo fila 4
@@ -239,6 +262,7 @@ pub fn generate_diorama() -> Vec<Cube> {
         min: Vec3::new(3.5, 2.0, 0.0),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.5 + cube_size, 2.0 + cube_size, cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::bamboo(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     //Mossy blocks
@@ -247,24 +271,28 @@ pub fn generate_diorama() -> Vec<Cube> {
         min: Vec3::new(0.0, 2.0, 3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(0.0 + cube_size, 2.0 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::moosy(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(0.0, 2.5, 3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(0.0 + cube_size, 2.5 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::moosy(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(0.0, 3.0 ,3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(0.0 + cube_size, 3.0 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::moosy(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(0.0, 3.5 ,3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(0.0 + cube_size, 3.5 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::moosy(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     //Fila 2
@@ -272,18 +300,21 @@ pub fn generate_diorama() -> Vec<Cube> {
         min: Vec3::new(0.5, 2.5, 3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(0.5 + cube_size, 2.5 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::moosy(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(0.5, 3.0 ,3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(0.5 + cube_size, 3.0 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::moosy(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(0.5, 3.5 ,3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(0.5 + cube_size, 3.5 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::moosy(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     //Fila 3
@@ -291,18 +322,14 @@ pub fn generate_diorama() -> Vec<Cube> {
         min: Vec3::new(1.0, 3.0 ,3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(1.0 + cube_size, 3.0 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::moosy(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(1.0, 3.5 ,3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(1.0 + cube_size, 3.5 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::moosy(),  // Material del cubo (o cualquier otro material que prefieras)
-    });
-
-    objects.push(Cube {
-        min: Vec3::new(1.0, 3.0 ,3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
-        max: Vec3::new(1.0 + cube_size, 3.0 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
-        material: Material::moosy(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     // Fila 4
@@ -310,6 +337,7 @@ pub fn generate_diorama() -> Vec<Cube> {
         min: Vec3::new(1.5, 3.5 ,3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(1.5 + cube_size, 3.5 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::moosy(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     //Moggsy blocks 2
@@ -317,24 +345,28 @@ pub fn generate_diorama() -> Vec<Cube> {
         min: Vec3::new(3.5, 1.0 ,3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.5 + cube_size, 1.0 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::moosy_block(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(3.5, 0.5 ,3.0),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.5 + cube_size, 0.5 + cube_size, 3.0 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::moosy_block(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(3.5, 0.5 ,2.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.5 + cube_size, 0.5 + cube_size, 2.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::moosy_block(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(3.0, 0.5 ,3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.0 + cube_size, 0.5 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::moosy_block(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     //Grass and dirt blocks
@@ -342,36 +374,42 @@ pub fn generate_diorama() -> Vec<Cube> {
         min: Vec3::new(3.5, 0.5 , 0.0),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.5 + cube_size, 0.5 + cube_size, 0.0 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::grass(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(3.0, 0.5 , 0.0),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.0 + cube_size, 0.5 + cube_size, 0.0 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::grass(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(3.5, 0.5 , 0.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.5 + cube_size, 0.5 + cube_size, 0.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::dirt(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(3.5, 0.5 , 1.0),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.5 + cube_size, 0.5 + cube_size, 1.0 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::dirt(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(3.5, 0.5 , 1.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.5 + cube_size, 0.5 + cube_size, 1.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::moosy(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(3.5, 1.0 , 0.0),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.5 + cube_size, 1.0 + cube_size, 0.0 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::concrete(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     //Esmeraldas y diamantes
@@ -379,12 +417,14 @@ pub fn generate_diorama() -> Vec<Cube> {
         min: Vec3::new(3.5, 2.5 , 2.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.5 + cube_size, 2.5 + cube_size, 2.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::emerald(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(3.5, 2.5 , 2.0),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(3.5 + cube_size, 2.5 + cube_size, 2.0 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::emerald(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
     
     //Diamantes
@@ -392,12 +432,14 @@ pub fn generate_diorama() -> Vec<Cube> {
         min: Vec3::new(2.5, 0.5 , 3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(2.5 + cube_size, 0.5 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::diamond(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });    
 
     objects.push(Cube {
         min: Vec3::new(2.0, 0.5 , 3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(2.0 + cube_size, 0.5 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::diamond(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });    
 
 
@@ -405,6 +447,7 @@ pub fn generate_diorama() -> Vec<Cube> {
         min: Vec3::new(2.0, 1.0, 3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(2.0 + cube_size, 1.0 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::diamond(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     //Concreto 
@@ -412,18 +455,21 @@ pub fn generate_diorama() -> Vec<Cube> {
         min: Vec3::new(0.0, 1.5, 3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(0.0 + cube_size, 1.5 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::concrete(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(0.0, 1.0, 3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(0.0 + cube_size, 1.0 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::concrete(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(0.0, 0.5, 3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(0.0 + cube_size, 0.5 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::concrete(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
     
     //Fila 2
@@ -431,12 +477,14 @@ pub fn generate_diorama() -> Vec<Cube> {
         min: Vec3::new(0.0, 1.0, 3.0),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(0.0 + cube_size, 1.0 + cube_size, 3.0 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::concrete(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(0.0, 0.5, 3.0),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(0.0 + cube_size, 0.5 + cube_size, 3.0 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::concrete(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     //Fila 3
@@ -444,6 +492,7 @@ pub fn generate_diorama() -> Vec<Cube> {
         min: Vec3::new(0.0, 0.5, 2.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(0.0 + cube_size, 0.5 + cube_size, 2.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::concrete(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
 
@@ -452,12 +501,14 @@ pub fn generate_diorama() -> Vec<Cube> {
         min: Vec3::new(2.0, 2.0, 3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(2.0 + cube_size, 2.0 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::glass(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(1.5, 2.0, 3.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(1.5 + cube_size, 2.0 + cube_size, 3.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::glass(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     //Lamp
@@ -465,6 +516,7 @@ pub fn generate_diorama() -> Vec<Cube> {
         min: Vec3::new(2.0, 3.5, 2.0),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(2.0 + cube_size, 3.5 + cube_size, 2.0 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::lamp(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     //Mesa
@@ -472,24 +524,28 @@ pub fn generate_diorama() -> Vec<Cube> {
         min: Vec3::new(0.0, 0.5, 0.0),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(0.0 + cube_size, 0.5 + cube_size, 0.0 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::terracota(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(0.5, 0.5, 0.0),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(0.5 + cube_size, 0.5 + cube_size, 0.0 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::terracota(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(0.0, 0.5, 0.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(0.0 + cube_size, 0.5 + cube_size, 0.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::terracota(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
     objects.push(Cube {
         min: Vec3::new(0.5, 0.5, 0.5),  // Coordenada mínima (X, Y, Z) donde quieres que inicie el cubo
         max: Vec3::new(0.5 + cube_size, 0.5 + cube_size, 0.5 + cube_size),  // Coordenada máxima, sumando el tamaño del cubo
         material: Material::terracota(),  // Material del cubo (o cualquier otro material que prefieras)
+        uv_tile: None,
     });
 
 
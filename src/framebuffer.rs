@@ -18,6 +18,17 @@ impl Framebuffer {
         }
     }
 
+    // Envuelve un buffer ya lleno (por ejemplo el escalado a la ventana) para poder dibujar encima
+    pub fn from_buffer(width: usize, height: usize, buffer: Vec<u32>) -> Self {
+        Framebuffer {
+            width,
+            height,
+            buffer,
+            background_color: 0x87CEEB,
+            current_color: 0xFFFFFF
+        }
+    }
+
     pub fn clear(&mut self) {
         for pixel in self.buffer.iter_mut() {
             *pixel = self.background_color;
@@ -30,6 +41,23 @@ impl Framebuffer {
         }
     }
 
+    // Línea de (x0, y0) a (x1, y1) con el color actual; lo que cae fuera de la imagen se descarta
+    pub fn line(&mut self, x0: f32, y0: f32, x1: f32, y1: f32) {
+        let steps = (x1 - x0).abs().max((y1 - y0).abs()).ceil();
+        // Una línea que se sale mucho de la imagen (un vértice casi pegado a la cámara) no se dibuja
+        if steps > 4.0 * (self.width + self.height) as f32 {
+            return;
+        }
+
+        for step in 0..=steps as usize {
+            let t = if steps > 0.0 { step as f32 / steps } else { 0.0 };
+            let (x, y) = (x0 + (x1 - x0) * t, y0 + (y1 - y0) * t);
+            if x >= 0.0 && y >= 0.0 {
+                self.point(x.round() as usize, y.round() as usize);
+            }
+        }
+    }
+
     pub fn set_background_color(&mut self, color: u32) {
         self.background_color = color;
     }
@@ -37,4 +65,16 @@ impl Framebuffer {
     pub fn set_current_color(&mut self, color: u32) {
         self.current_color = color;
     }
+
+    pub fn to_image(&self) -> image::RgbImage {
+        image::RgbImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+            let color = self.buffer[y as usize * self.width + x as usize];
+            image::Rgb([(color >> 16) as u8, (color >> 8) as u8, color as u8])
+        })
+    }
+
+    // Guarda el contenido como imagen; el formato sale de la extensión (png, jpg, tga, ...)
+    pub fn save(&self, path: &str) -> image::ImageResult<()> {
+        self.to_image().save(path)
+    }
 }
@@ -0,0 +1,107 @@
+use nalgebra_glm::Vec3;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// Framebuffer de alto rango dinámico: radiancia RGB lineal en `f32`, sin recortar a 0..255.
+/// Sirve para guardar renders en `.exr` o `.hdr` y componerlos después sin perder los brillos.
+pub struct HdrFramebuffer {
+    pub width: usize,
+    pub height: usize,
+    pub buffer: Vec<Vec3>,
+}
+
+impl HdrFramebuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        HdrFramebuffer { width, height, buffer: vec![Vec3::zeros(); width * height] }
+    }
+
+    // Guarda según la extensión: `.exr` (OpenEXR) o `.hdr` (Radiance RGBE)
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let extension = path.rsplit_once('.').map(|(_, ext)| ext.to_lowercase()).unwrap_or_default();
+        let result = match extension.as_str() {
+            "exr" => self.write_exr(path),
+            "hdr" => self.write_hdr(path),
+            _ => return Err(format!("Formato HDR desconocido: {} (usa .exr o .hdr)", path)),
+        };
+        result.map_err(|e| format!("No se pudo guardar {}: {}", path, e))
+    }
+
+    fn write_hdr(&self, path: &str) -> Result<(), String> {
+        let file = File::create(path).map_err(|e| e.to_string())?;
+        let pixels: Vec<image::Rgb<f32>> = self.buffer.iter().map(|c| image::Rgb([c.x, c.y, c.z])).collect();
+        image::codecs::hdr::HdrEncoder::new(BufWriter::new(file))
+            .encode(&pixels, self.width, self.height)
+            .map_err(|e| e.to_string())
+    }
+
+    // OpenEXR de una sola parte, por líneas, sin compresión y con canales `float` de 32 bits
+    fn write_exr(&self, path: &str) -> Result<(), String> {
+        let (width, height) = (self.width as i32, self.height as i32);
+        let mut header = Vec::new();
+
+        // Número mágico y versión 2 (imagen por líneas, sin banderas)
+        header.extend_from_slice(&[0x76, 0x2f, 0x31, 0x01]);
+        header.extend_from_slice(&2u32.to_le_bytes());
+
+        // Los canales van en orden alfabético: B, G, R
+        let mut channels = Vec::new();
+        for name in ["B", "G", "R"] {
+            channels.extend_from_slice(name.as_bytes());
+            channels.push(0);
+            channels.extend_from_slice(&2i32.to_le_bytes());  // FLOAT
+            channels.extend_from_slice(&[0, 0, 0, 0]);        // pLinear y reservado
+            channels.extend_from_slice(&1i32.to_le_bytes());  // xSampling
+            channels.extend_from_slice(&1i32.to_le_bytes());  // ySampling
+        }
+        channels.push(0);
+        attribute(&mut header, "channels", "chlist", &channels);
+
+        attribute(&mut header, "compression", "compression", &[0]);  // Sin compresión
+        let window: Vec<u8> = [0, 0, width - 1, height - 1].iter().flat_map(|v| v.to_le_bytes()).collect();
+        attribute(&mut header, "dataWindow", "box2i", &window);
+        attribute(&mut header, "displayWindow", "box2i", &window);
+        attribute(&mut header, "lineOrder", "lineOrder", &[0]);  // De arriba hacia abajo
+        attribute(&mut header, "pixelAspectRatio", "float", &1.0f32.to_le_bytes());
+        attribute(&mut header, "screenWindowCenter", "v2f", &[0u8; 8]);
+        attribute(&mut header, "screenWindowWidth", "float", &1.0f32.to_le_bytes());
+        header.push(0);
+
+        // Tabla con la posición de cada línea dentro del archivo; cada bloque es
+        // y (i32) + tamaño (i32) + los valores de B, G y R de la línea
+        let line_size = self.width * 3 * 4;
+        let first_line = header.len() + self.height * 8;
+        for y in 0..self.height {
+            header.extend_from_slice(&((first_line + y * (line_size + 8)) as u64).to_le_bytes());
+        }
+
+        let file = File::create(path).map_err(|e| e.to_string())?;
+        let mut writer = BufWriter::new(file);
+        writer.write_all(&header).map_err(|e| e.to_string())?;
+
+        let mut line = Vec::with_capacity(line_size + 8);
+        for y in 0..self.height {
+            line.clear();
+            line.extend_from_slice(&(y as i32).to_le_bytes());
+            line.extend_from_slice(&(line_size as i32).to_le_bytes());
+            let row = &self.buffer[y * self.width..(y + 1) * self.width];
+            for channel in [2, 1, 0] {
+                for color in row {
+                    line.extend_from_slice(&color[channel].to_le_bytes());
+                }
+            }
+            writer.write_all(&line).map_err(|e| e.to_string())?;
+        }
+
+        writer.flush().map_err(|e| e.to_string())
+    }
+}
+
+// Atributo del encabezado de OpenEXR: nombre, tipo, tamaño y valor
+fn attribute(header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    header.extend_from_slice(name.as_bytes());
+    header.push(0);
+    header.extend_from_slice(kind.as_bytes());
+    header.push(0);
+    header.extend_from_slice(&(value.len() as i32).to_le_bytes());
+    header.extend_from_slice(value);
+}
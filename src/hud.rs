@@ -0,0 +1,136 @@
+use nalgebra_glm::Vec3;
+use crate::camera::Camera;
+use crate::cube::Cube;
+use crate::framebuffer::Framebuffer;
+
+// Tamaño de cada letra de la fuente en píxeles, antes de escalar
+const GLYPH_WIDTH: usize = 5;
+const GLYPH_HEIGHT: usize = 7;
+
+/// Dibuja líneas de texto sobre un panel oscurecido con la esquina superior izquierda en (x, y),
+/// con letras de `scale` x `scale` píxeles por punto de la fuente.
+pub fn draw_panel(framebuffer: &mut Framebuffer, x: usize, y: usize, lines: &[String], scale: usize) {
+    let margin = 4 * scale;
+    let line_height = (GLYPH_HEIGHT + 3) * scale;
+    let (width, height) = panel_size(lines, scale);
+
+    // Panel a media luz para que el texto se lea sobre cualquier fondo
+    for row in y..(y + height).min(framebuffer.height) {
+        let start = row * framebuffer.width + x.min(framebuffer.width);
+        let end = row * framebuffer.width + (x + width).min(framebuffer.width);
+        for pixel in &mut framebuffer.buffer[start..end] {
+            *pixel = (*pixel >> 1) & 0x7F7F7F;
+        }
+    }
+
+    for (index, line) in lines.iter().enumerate() {
+        draw_text(framebuffer, x + margin, y + margin + index * line_height, line, scale, 0xFFFFFF);
+    }
+}
+
+// Ancho y alto del panel de `draw_panel`, para poder ubicarlo en otra esquina
+pub fn panel_size(lines: &[String], scale: usize) -> (usize, usize) {
+    let longest = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+    (8 * scale + longest * (GLYPH_WIDTH + 1) * scale, 8 * scale + lines.len() * (GLYPH_HEIGHT + 3) * scale)
+}
+
+// Contorno de un cubo: sus 12 aristas proyectadas con la cámara sobre una imagen del tamaño de `framebuffer`
+pub fn draw_outline(framebuffer: &mut Framebuffer, camera: &Camera, cube: &Cube, color: u32) {
+    let (width, height) = (framebuffer.width as f32, framebuffer.height as f32);
+    let corner = |i: usize| Vec3::new(
+        if i & 1 == 0 { cube.min.x } else { cube.max.x },
+        if i & 2 == 0 { cube.min.y } else { cube.max.y },
+        if i & 4 == 0 { cube.min.z } else { cube.max.z },
+    );
+
+    framebuffer.set_current_color(color);
+    for a in 0..8 {
+        // Cada arista une dos esquinas que difieren en un solo eje
+        for axis in [1, 2, 4] {
+            if a & axis != 0 {
+                continue;
+            }
+            let projected = (camera.project(&corner(a), width, height), camera.project(&corner(a | axis), width, height));
+            if let (Some((x0, y0)), Some((x1, y1))) = projected {
+                framebuffer.line(x0, y0, x1, y1);
+            }
+        }
+    }
+}
+
+// Escribe `text` con la esquina superior izquierda en (x, y); las minúsculas se dibujan como mayúsculas
+pub fn draw_text(framebuffer: &mut Framebuffer, x: usize, y: usize, text: &str, scale: usize, color: u32) {
+    framebuffer.set_current_color(color);
+    for (index, character) in text.chars().enumerate() {
+        let left = x + index * (GLYPH_WIDTH + 1) * scale;
+        for (row, bits) in glyph(character.to_ascii_uppercase()).iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                if bits & (0x10 >> column) == 0 {
+                    continue;
+                }
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        framebuffer.point(left + column * scale + dx, y + row * scale + dy);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Fuente de 5 x 7: una fila por byte, el bit 4 es la columna de la izquierda
+fn glyph(character: char) -> [u8; GLYPH_HEIGHT] {
+    match character {
+        ' ' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        'A' => [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        ',' => [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '+' => [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00],
+        '=' => [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00],
+        '/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+        '(' => [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02],
+        ')' => [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08],
+        '%' => [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03],
+        '#' => [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A],
+        '_' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F],
+        // Cualquier otro carácter (incluidos los acentos) se dibuja como '?'
+        _ => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+    }
+}
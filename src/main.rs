@@ -2,56 +2,494 @@ mod framebuffer;
 mod ray_intersect;
 mod cube;
 mod color;
+// Sin la feature `viewer` quedan partes de estos módulos que solo usa el visor
+#[cfg_attr(not(feature = "viewer"), allow(dead_code))]
 mod camera;
 mod light;
 mod material;
 mod texture;
 mod diorama;
 mod constants;
+mod optimize;
+mod validate;
+mod noise;
+mod terrain;
+mod scene;
+mod structures;
+mod world;
+#[cfg_attr(not(feature = "viewer"), allow(dead_code))]
+mod path;
+#[cfg_attr(not(feature = "viewer"), allow(dead_code))]
+mod bookmarks;
+mod stereo;
+mod hdr;
+mod aov;
+mod animation;
+#[cfg(feature = "viewer")]
+mod screenshot;
+mod sampling;
+#[cfg_attr(not(feature = "viewer"), allow(dead_code))]
+mod upscale;
+#[cfg(feature = "viewer")]
+mod resolution;
+mod config;
+#[cfg(feature = "viewer")]
+mod hud;
 
-use minifb::{ Window, WindowOptions, Key };
 use nalgebra_glm::Vec3;
-use std::time::Duration;
-use std::f32::consts::PI;
 use crate::color::Color;
 use crate::framebuffer::Framebuffer;
-use crate::camera::Camera;
+use crate::hdr::HdrFramebuffer;
+use crate::aov::{Aov, AovBuffers};
+use crate::animation::{Animation, AnimationWriter, CameraMotion, LightMotion};
+use crate::camera::{Camera, Projection};
+use crate::sampling::{render_samples, Filter, Pattern, Sampling};
+use crate::config::{Config, Environment};
 use crate::light::Light;
-use std::time::Instant;
-use crate::diorama::generate_diorama;
+use crate::material::Material;
+use crate::scene::SceneSpec;
+use crate::path::CameraPath;
+use crate::bookmarks::Bookmarks;
+use crate::stereo::{render_stereo, StereoLayout, StereoParams};
 use rayon::prelude::*;
-use crate::ray_intersect::{Intersect, RayIntersect};
-use crate::texture::{calculate_uv, get_texture_color};
-use crate::cube::Cube;
+use crate::ray_intersect::{Intersect, SceneIntersect};
+use crate::texture::{get_texture_color, scroll_uv};
+
+// Lo que solo usa el visor interactivo
+#[cfg(feature = "viewer")]
+use minifb::{ Window, WindowOptions, Key, KeyRepeat, MouseButton, MouseMode };
+#[cfg(feature = "viewer")]
+use std::f32::consts::PI;
+#[cfg(feature = "viewer")]
+use std::time::Instant;
+#[cfg(feature = "viewer")]
+use crate::camera::CameraMode;
+#[cfg(feature = "viewer")]
+use crate::scene::Geometry;
+#[cfg(feature = "viewer")]
+use crate::screenshot::{save_buffer, screenshot_path};
+#[cfg(feature = "viewer")]
+use crate::upscale::{sharpen, upscale_framebuffer};
+#[cfg(feature = "viewer")]
+use crate::resolution::DynamicResolution;
+#[cfg(feature = "viewer")]
+use crate::hud::{draw_outline, draw_panel, panel_size};
+#[cfg(feature = "audio")]
 use rodio::{Decoder, OutputStream, Sink, Source};
-use std::fs::File;
-use std::io::BufReader;
 
 fn main() {
-    // Crear un "output stream" para el audio
-    let (_stream, stream_handle) = OutputStream::try_default().unwrap();
-    let sink = Sink::try_new(&stream_handle).unwrap();
+    let args: Vec<String> = std::env::args().skip(1).collect();
 
-    // Cargar el archivo de música
-    let file = File::open("assets/music.wav").unwrap();  // Cambia el nombre del archivo de música
-    let source = Decoder::new(BufReader::new(file)).unwrap();
+    // El primer argumento puede ser un subcomando; el resto son opciones `--clave valor`
+    let (command, flags) = match args.first() {
+        Some(first) if !first.starts_with("--") => (Some(first.as_str()), &args[1..]),
+        _ => (None, &args[..]),
+    };
 
-    // Hacer que el archivo de música se repita indefinidamente
-    let repeated_source = source.repeat_infinite();
-    sink.append(repeated_source);
+    let flags = flags.to_vec();
+    let result = match command {
+        Some("validate") => SceneSpec::from_args(&flags).map(|spec| run_validate(&spec)),
+        Some("animate") => run_animate(flags),
+        Some("render") => run_render(flags),
+        Some("panorama") => run_panorama(flags),
+        Some(other) => Err(format!("Subcomando desconocido: {} (disponibles: validate, render, animate, panorama)", other)),
+        #[cfg(feature = "viewer")]
+        None => run_viewer(flags).map(|()| 0),
+        #[cfg(not(feature = "viewer"))]
+        None => Err("Compilado sin la feature viewer: usa validate, render, animate o panorama".to_string()),
+    };
 
-    // Controlar el volumen
-    sink.set_volume(1.5);
+    match result {
+        Ok(0) => {}
+        Ok(code) => std::process::exit(code),
+        Err(e) => {
+            println!("{}", e);
+            std::process::exit(2);
+        }
+    }
+}
 
-    let window_width = 800;
-    let window_height = 600;
-    
-    // Reducimos el tamaño del framebuffer a la mitad de la ventana
-    let framebuffer_scale_factor = constants::FRAMEBUFFER_SCALE_FACTOR;  // Renderizamos a la mitad de la resolución
-    let framebuffer_width = (window_width as f32 * framebuffer_scale_factor) as usize;
-    let framebuffer_height = (window_height as f32 * framebuffer_scale_factor) as usize;
-    
+// Saca `nombre valor` de las opciones y devuelve el valor, para que el resto se lo lleve `SceneSpec`
+fn take_flag(flags: &mut Vec<String>, name: &str) -> Result<Option<String>, String> {
+    let Some(index) = flags.iter().position(|flag| flag == name) else {
+        return Ok(None);
+    };
+    if index + 1 >= flags.len() {
+        return Err(format!("Falta el valor de {}", name));
+    }
+    let value = flags.remove(index + 1);
+    flags.remove(index);
+    Ok(Some(value))
+}
+
+// Saca una opción sin valor (`--cubemap`) y dice si estaba
+fn take_switch(flags: &mut Vec<String>, name: &str) -> bool {
+    match flags.iter().position(|flag| flag == name) {
+        Some(index) => {
+            flags.remove(index);
+            true
+        }
+        None => false,
+    }
+}
+
+// Como `take_flag`, pero convierte el valor y usa `default` si la opción no está
+fn take_parsed<T: std::str::FromStr>(flags: &mut Vec<String>, name: &str, default: T) -> Result<T, String> {
+    match take_flag(flags, name)? {
+        Some(value) => value.parse().map_err(|_| format!("Valor inválido para {}: {}", name, value)),
+        None => Ok(default),
+    }
+}
+
+// Como `take_parsed`, para medidas en píxeles: una imagen sin ancho o sin alto no se puede renderizar
+fn take_size(flags: &mut Vec<String>, name: &str, default: usize) -> Result<usize, String> {
+    match take_parsed(flags, name, default)? {
+        0 => Err(format!("{} debe ser mayor que 0", name)),
+        size => Ok(size),
+    }
+}
+
+// Archivo de configuración que se lee si existe y no se pasa `--config`
+const CONFIG_FILE: &str = "settings.conf";
+
+// Ajustes de `--config ARCHIVO` (o de `settings.conf` si existe) y encima las opciones `--clave valor`
+// con los nombres de `Config::KEYS`. Sin `viewer` (los subcomandos que guardan archivos) las opciones
+// del visor son un error y las del archivo se ignoran avisando
+fn take_config(flags: &mut Vec<String>, viewer: bool) -> Result<Config, String> {
+    let file = match take_flag(flags, "--config")? {
+        Some(file) => Some(file),
+        None => std::path::Path::new(CONFIG_FILE).exists().then(|| CONFIG_FILE.to_string()),
+    };
+
+    let mut config = Config::default();
+    if let Some(file) = file {
+        let (loaded, keys) = Config::load(&file)?;
+        config = loaded;
+        println!("Configuración leída de {}", file);
+
+        let ignored: Vec<String> = keys.into_iter().filter(|key| Config::VIEWER_KEYS.contains(&key.as_str())).collect();
+        if !viewer && !ignored.is_empty() {
+            println!("  Solo para el visor, se ignoran: {}", ignored.join(", "));
+        }
+    }
+
+    for key in Config::KEYS {
+        if let Some(value) = take_flag(flags, &format!("--{}", key))? {
+            if !viewer && Config::VIEWER_KEYS.contains(&key) {
+                return Err(format!("--{} solo se usa en el visor", key));
+            }
+            config.set(key, &value)?;
+        }
+    }
+    Ok(config)
+}
+
+// Antialiasing de los subcomandos: `--samples N` rayos por píxel, `--jitter` para repartirlos al azar
+// dentro de cada celda, `--filter box|tent|gaussian|mitchell` y `--adaptive UMBRAL` para lanzarlos
+// solo donde los píxeles vecinos difieren
+fn take_sampling(flags: &mut Vec<String>) -> Result<Sampling, String> {
+    let mut sampling = Sampling::new(take_parsed(flags, "--samples", 1)?);
+    if take_switch(flags, "--jitter") {
+        sampling.pattern = Pattern::Jittered;
+    }
+    if let Some(name) = take_flag(flags, "--filter")? {
+        sampling.filter = Filter::parse(&name)?;
+    }
+    sampling.adaptive = take_flag(flags, "--adaptive")?
+        .map(|v| v.parse().map_err(|_| format!("Valor inválido para --adaptive: {}", v))).transpose()?;
+    if sampling.adaptive.is_some() && sampling.grid < 2 {
+        return Err("--adaptive necesita --samples de 4 o más".to_string());
+    }
+    Ok(sampling)
+}
+
+// Subcomando `validate`: imprime todos los problemas de la escena y devuelve el código de salida
+fn run_validate(spec: &SceneSpec) -> i32 {
+    let issues = spec.validate();
+    for issue in &issues {
+        println!("{}", issue);
+    }
+
+    if issues.is_empty() {
+        println!("Escena válida");
+        0
+    } else {
+        println!("{} problemas encontrados", issues.len());
+        1
+    }
+}
+
+// Subcomando `render`: renderiza una imagen fija directo a un archivo, sin ventana ni audio.
+// El formato sale de la extensión de `--output` (png, jpg o tga; exr o hdr para alto rango dinámico)
+fn run_render(mut flags: Vec<String>) -> Result<i32, String> {
+    let output = take_flag(&mut flags, "--output")?.unwrap_or_else(|| "render.png".to_string());
+    let width = take_size(&mut flags, "--width", 800)?;
+    let height = take_size(&mut flags, "--height", 600)?;
+    let sampling = take_sampling(&mut flags)?;
+    let stereo_layout = take_flag(&mut flags, "--stereo")?.map(|name| StereoLayout::parse(&name)).transpose()?;
+    let aovs = take_flag(&mut flags, "--aov")?.map(|names| Aov::parse_list(&names)).transpose()?.unwrap_or_default();
+    let camera_flags = CameraFlags::take(&mut flags)?;
+    let config = take_config(&mut flags, false)?;
+    let spec = SceneSpec::from_args(&flags)?;
+
+    let scene = spec.load();
+    let environment = config.environment;
+    let light = Light::new(config.light_position.unwrap_or(scene.light_position), Color::new(128, 255, 128), config.intensity);
+    let mut camera = scene.camera;
+    camera_flags.apply(&mut camera, &spec, config.view_height)?;
+
+    // Cada pasada pedida va a su propio archivo junto a la imagen: `render_depth.png`, `render_normal.png`, ...
+    if !aovs.is_empty() {
+        let buffers = AovBuffers::render(&scene.objects, &camera, &light, &environment, width, height);
+        let (stem, extension) = output.rsplit_once('.').unwrap_or((output.as_str(), "png"));
+        for aov in aovs {
+            let file = format!("{}_{}.{}", stem, aov.name(), extension);
+            buffers.save(aov, &file)?;
+            match aov {
+                Aov::Tests => println!("Pasada {} guardada en {} (hasta {} pruebas por rayo)", aov.name(), file, buffers.max_tests()),
+                _ => println!("Pasada {} guardada en {}", aov.name(), file),
+            }
+        }
+    }
+
+    // `.exr` y `.hdr` guardan radiancia lineal sin recortar
+    let extension = output.rsplit_once('.').map(|(_, ext)| ext.to_lowercase()).unwrap_or_default();
+    if extension == "exr" || extension == "hdr" {
+        if stereo_layout.is_some() {
+            return Err("El estéreo solo se puede guardar en imágenes de 8 bits (png, jpg o tga)".to_string());
+        }
+        let mut framebuffer = HdrFramebuffer::new(width, height);
+        render_hdr(&mut framebuffer, &scene.objects, &camera, &light, &environment, &sampling);
+        framebuffer.save(&output)?;
+        println!("Imagen HDR guardada en {}", output);
+        return Ok(0);
+    }
+
+    let mut framebuffer = Framebuffer::new(width, height);
+    framebuffer.clear();
+    match stereo_layout {
+        Some(layout) => render_stereo(&mut framebuffer, &scene.objects, &camera, &light, &environment, &StereoParams::new(layout, camera.focus_distance))?,
+        None => render_supersampled(&mut framebuffer, &scene.objects, &camera, &light, &environment, &sampling),
+    }
+
+    framebuffer.save(&output).map_err(|e| format!("No se pudo guardar {}: {}", output, e))?;
+    println!("Imagen guardada en {}", output);
+    Ok(0)
+}
+
+// Opciones de cámara de la línea de comandos; lo que no se pase queda como en la escena
+struct CameraFlags {
+    eye: Option<Vec3>,
+    center: Option<Vec3>,
+    fov: Option<f32>,
+    projection: Option<String>,
+    view_height: Option<f32>,
+    bookmark: Option<usize>,
+}
+
+impl CameraFlags {
+    // Saca `--eye X,Y,Z`, `--center X,Y,Z`, `--fov GRADOS`, `--projection perspective|orthographic|isometric`,
+    // `--view-height ALTO` (lo que se ve en vertical en ortográfica) y `--bookmark N` (una vista guardada de la escena)
+    fn take(flags: &mut Vec<String>) -> Result<CameraFlags, String> {
+        let vector = |name: &str, value: Option<String>| -> Result<Option<Vec3>, String> {
+            let Some(value) = value else {
+                return Ok(None);
+            };
+            let parts: Vec<f32> = value.split(',').map(|v| v.trim().parse()).collect::<Result<_, _>>()
+                .map_err(|_| format!("Valor inválido para {}: {} (usa X,Y,Z)", name, value))?;
+            match parts[..] {
+                [x, y, z] => Ok(Some(Vec3::new(x, y, z))),
+                _ => Err(format!("Valor inválido para {}: {} (usa X,Y,Z)", name, value)),
+            }
+        };
+
+        Ok(CameraFlags {
+            eye: vector("--eye", take_flag(flags, "--eye")?)?,
+            center: vector("--center", take_flag(flags, "--center")?)?,
+            fov: take_flag(flags, "--fov")?.map(|v| v.parse().map_err(|_| format!("Valor inválido para --fov: {}", v))).transpose()?,
+            projection: take_flag(flags, "--projection")?,
+            view_height: take_flag(flags, "--view-height")?
+                .map(|v| v.parse().ok().filter(|&h: &f32| h > 0.0).ok_or(format!("Valor inválido para --view-height: {}", v))).transpose()?,
+            bookmark: take_flag(flags, "--bookmark")?.map(|v| v.parse().map_err(|_| format!("Valor inválido para --bookmark: {}", v))).transpose()?,
+        })
+    }
+
+    // `view_height` es el ajuste `view-height` de la configuración; `--view-height` tiene prioridad
+    fn apply(&self, camera: &mut Camera, spec: &SceneSpec, view_height: Option<f32>) -> Result<(), String> {
+        if let Some(slot) = self.bookmark {
+            let file = format!("{}.bookmarks", spec.name());
+            if !Bookmarks::load(&file)?.recall(slot, camera) {
+                return Err(format!("La vista {} no está guardada en {}", slot, file));
+            }
+        }
+        if let Some(eye) = self.eye {
+            camera.eye = eye;
+        }
+        if let Some(center) = self.center {
+            camera.center = center;
+        }
+        if let Some(fov) = self.fov {
+            camera.fov = fov.to_radians();
+        }
+        camera.focus_distance = (camera.center - camera.eye).magnitude();
+
+        match self.projection.as_deref() {
+            None | Some("perspective") => {}
+            Some("orthographic") => camera.toggle_projection(),
+            Some("isometric") => camera.isometric(),
+            Some(other) => return Err(format!("Proyección desconocida: {} (usa perspective, orthographic o isometric)", other)),
+        }
+
+        if let Some(height) = self.view_height.or(view_height) {
+            if self.view_height.is_some() && !matches!(camera.projection, Projection::Orthographic { .. }) {
+                return Err("--view-height solo se usa con --projection orthographic o isometric".to_string());
+            }
+            camera.set_view_height(height);
+        }
+        Ok(())
+    }
+}
+
+// Subcomando `animate`: renderiza una animación cuadro por cuadro, sin ventana ni audio. La cámara
+// sigue el recorrido de `--path` o, si no hay, gira alrededor de la escena; `--light-orbit` mueve la luz
+// y las texturas animadas avanzan con el tiempo de cada cuadro.
+// La salida es una carpeta de PNG numerados, un `.gif` o un `.apng`
+fn run_animate(mut flags: Vec<String>) -> Result<i32, String> {
+    let path_file = take_flag(&mut flags, "--path")?;
+    let output = take_flag(&mut flags, "--output")?.unwrap_or_else(|| "frames".to_string());
+    let fps: f32 = take_parsed(&mut flags, "--fps", 30.0)?;
+    let width = take_size(&mut flags, "--width", 800)?;
+    let height = take_size(&mut flags, "--height", 600)?;
+    let sampling = take_sampling(&mut flags)?;
+    let turns: f32 = take_parsed(&mut flags, "--turns", 1.0)?;
+    let duration: Option<f32> = take_flag(&mut flags, "--duration")?
+        .map(|v| v.parse().map_err(|_| format!("Valor inválido para --duration: {}", v))).transpose()?;
+    let light = if take_switch(&mut flags, "--light-orbit") { LightMotion::Orbit } else { LightMotion::Still };
+    let camera_flags = CameraFlags::take(&mut flags)?;
+    let config = take_config(&mut flags, false)?;
+    let spec = SceneSpec::from_args(&flags)?;
+
+    if fps <= 0.0 {
+        return Err("--fps debe ser mayor que 0".to_string());
+    }
+
+    let animation = match path_file {
+        Some(file) => {
+            let camera_path = CameraPath::load(&file)?;
+            if camera_path.keyframes.is_empty() {
+                return Err(format!("El recorrido {} no tiene keyframes", file));
+            }
+            Animation { fps, duration: duration.unwrap_or(camera_path.duration()), camera: CameraMotion::Path(camera_path), light }
+        }
+        None => Animation { fps, duration: duration.unwrap_or(6.0), camera: CameraMotion::Turntable { turns }, light },
+    };
+
+    let scene = spec.load();
+    let mut camera = scene.camera;
+    camera_flags.apply(&mut camera, &spec, config.view_height)?;
+
+    let mut writer = AnimationWriter::create(&output, fps)?;
+    let mut framebuffer = Framebuffer::new(width, height);
+    let frames = animation.frame_count();
+    for frame in 0..frames {
+        let (frame_camera, light_position) = animation.frame(frame, &camera, config.light_position.unwrap_or(scene.light_position));
+        let light = Light::new(light_position, Color::new(128, 255, 128), config.intensity);
+        let environment = Environment { time: animation.time(frame), ..config.environment };
+
+        framebuffer.clear();
+        render_supersampled(&mut framebuffer, &scene.objects, &frame_camera, &light, &environment, &sampling);
+        writer.push(&framebuffer)?;
+        println!("Cuadro {}/{}", frame + 1, frames);
+    }
+
+    println!("Animación guardada en {}", writer.finish()?);
+    Ok(0)
+}
+
+// Subcomando `panorama`: renderiza todo lo que rodea al ojo de la cámara como imagen equirectangular
+// (el doble de ancha que alta) o como las seis caras de un cubemap
+fn run_panorama(mut flags: Vec<String>) -> Result<i32, String> {
+    let output = take_flag(&mut flags, "--output")?.unwrap_or_else(|| "panorama.png".to_string());
+    let width = take_size(&mut flags, "--width", 2048)?;
+    let cubemap = take_switch(&mut flags, "--cubemap");
+    let face_size = take_size(&mut flags, "--face-size", 512)?;
+    let camera_flags = CameraFlags::take(&mut flags)?;
+    let config = take_config(&mut flags, false)?;
+    let spec = SceneSpec::from_args(&flags)?;
+
+    // El panorama es el doble de ancho que de alto
+    if !cubemap && width < 2 {
+        return Err("--width de un panorama debe ser al menos 2".to_string());
+    }
+
+    let mut scene = spec.load();
+    let light = Light::new(config.light_position.unwrap_or(scene.light_position), Color::new(128, 255, 128), config.intensity);
+    camera_flags.apply(&mut scene.camera, &spec, config.view_height)?;
+
+    let mut outputs = Vec::new();
+    if cubemap {
+        // Cada cara va a su propio archivo: `panorama_px.png`, `panorama_nx.png`, ...
+        let (stem, extension) = output.rsplit_once('.').unwrap_or((output.as_str(), "png"));
+        for (face, camera) in scene.camera.cubemap_faces() {
+            outputs.push((format!("{}_{}.{}", stem, face, extension), camera, face_size, face_size));
+        }
+    } else {
+        let mut camera = scene.camera.leveled();
+        camera.projection = Projection::Equirectangular;
+        outputs.push((output.clone(), camera, width, width / 2));
+    }
+
+    for (file, camera, width, height) in outputs {
+        let mut framebuffer = Framebuffer::new(width, height);
+        framebuffer.clear();
+        render(&mut framebuffer, &scene.objects, &camera, &light, &config.environment);
+        framebuffer.save(&file).map_err(|e| format!("No se pudo guardar {}: {}", file, e))?;
+        println!("Panorama guardado en {}", file);
+    }
+
+    Ok(0)
+}
+
+#[cfg(feature = "viewer")]
+fn run_viewer(mut flags: Vec<String>) -> Result<(), String> {
+    let path_file = take_flag(&mut flags, "--path")?.unwrap_or_else(|| "camera.path".to_string());
+    let stereo_layout = take_flag(&mut flags, "--stereo")?.map(|name| StereoLayout::parse(&name)).transpose()?;
+    let config = take_config(&mut flags, true)?;
+    let spec = SceneSpec::from_args(&flags)?;
+
+    // El recorrido de cámara se carga si el archivo ya existe; si no, se empieza uno vacío
+    let mut camera_path = if std::path::Path::new(&path_file).exists() {
+        CameraPath::load(&path_file)?
+    } else {
+        CameraPath::default()
+    };
+    let mut playback: Option<f32> = None;  // Segundos reproducidos del recorrido
+
+    // Vistas guardadas de esta escena, en un archivo con el nombre de la escena
+    let bookmarks_file = format!("{}.bookmarks", spec.name());
+    let mut bookmarks = if std::path::Path::new(&bookmarks_file).exists() {
+        Bookmarks::load(&bookmarks_file)?
+    } else {
+        Bookmarks::default()
+    };
+
+    // La música es opcional: sin dispositivo de audio, sin el archivo o sin la feature `audio`
+    // el visor sigue en silencio
+    #[cfg(feature = "audio")]
+    let music = start_music(&config.music);
+
+    let window_width = config.window_width;
+    let window_height = config.window_height;
+    let environment = config.environment;
+    let mut upscale_filter = config.upscale;
+    let mut sharpness = config.sharpen;
+
+    // Renderizamos a menor resolución que la ventana; la escala empieza en `scale`
+    // y se ajusta sola cada cuadro para acercarse a `target-fps`
+    let mut resolution = DynamicResolution::new(config.target_fps, config.min_scale, config.max_scale, config.scale)?;
+    let (framebuffer_width, framebuffer_height) = resolution.framebuffer_size(window_width, window_height);
     let mut framebuffer = Framebuffer::new(framebuffer_width, framebuffer_height);
+    let mut last_pose = None;  // Pose de la cámara en el cuadro anterior, para saber si se está moviendo
 
     let mut window = Window::new(
         "Diorama - Upscaled",
@@ -60,23 +498,30 @@ fn main() {
         WindowOptions::default(),
     ).unwrap();
 
-    // Llamar a la función que genera el diorama manualmente
-    let objects = generate_diorama();
+    // Generar la escena elegida (el diorama por defecto) junto con su cámara y luz
+    let scene = spec.load();
+    let objects = scene.objects;
 
     let mut light = Light::new(
-        Vec3::new(2.5, 3.0, 2.5),  // Ajusta la posición de la luz
+        config.light_position.unwrap_or(scene.light_position),
         Color::new(128, 255, 128),   // Color de la luz
-        constants::INTENSITY  // Intensidad de la luz
-    );                    
+        config.intensity  // Intensidad de la luz
+    );
 
     let rotation_speed = PI/5.0;
     let mut last_frame_time = Instant::now();
 
-    let mut camera = Camera::new(
-        Vec3::new(0.0, 10.0, -10.0),  // Cambiamos la posición de la cámara (más alto y alejado)
-        Vec3::new(0.0, 5.0, 0.0),     // Apuntamos hacia el centro del diorama
-        Vec3::new(0.0, 1.0, 0.0),     // Vector "up", ajustado para la orientación
-    );       
+    let mut camera = scene.camera;
+    let mut stereo = stereo_layout.map(|layout| StereoParams::new(layout, camera.focus_distance));
+    let mut shown_aov: Option<Aov> = None;  // Pasada que se muestra en lugar de la imagen final
+    let mut antialiasing: Option<Sampling> = None;
+    let mut show_hud = false;
+    let mut show_picking = true;
+    let mut average_frame_time = 1.0 / 60.0;  // Segundos por cuadro, suavizado para que el HUD se pueda leer
+    let mut camera_mode = CameraMode::Orbit;
+    let mut fly_speed = 3.0;  // Unidades por segundo en vuelo libre
+    let mouse_sensitivity = 0.005;  // Radianes por píxel de movimiento del mouse
+    let mut last_mouse: Option<(f32, f32)> = None;
 
     while window.is_open() && !window.is_key_down(Key::Escape) {
         // Calcula delta time
@@ -85,19 +530,174 @@ fn main() {
         last_frame_time = current_time;
     
         let adjusted_rotation_speed = rotation_speed * delta_time;
+        average_frame_time += (delta_time - average_frame_time) * 0.1;
     
+        // Cambiar entre órbita y vuelo libre con 'F'; ambos modos usan el mismo ojo y centro,
+        // así que la vista no salta al cambiar
+        if window.is_key_pressed(Key::F, KeyRepeat::No) {
+            camera_mode = match camera_mode {
+                CameraMode::Orbit => CameraMode::Fly,
+                CameraMode::Fly => CameraMode::Orbit,
+            };
+        }
+
         // Movimientos de la cámara usando el delta time
-        if window.is_key_down(Key::A) {
-            camera.orbit(adjusted_rotation_speed, 0.0);
+        match camera_mode {
+            CameraMode::Orbit => {
+                if window.is_key_down(Key::A) {
+                    camera.orbit(adjusted_rotation_speed, 0.0);
+                }
+                if window.is_key_down(Key::D) {
+                    camera.orbit(-adjusted_rotation_speed, 0.0);
+                }
+                if window.is_key_down(Key::W) {
+                    camera.orbit(0.0, -adjusted_rotation_speed);
+                }
+                if window.is_key_down(Key::S) {
+                    camera.orbit(0.0, adjusted_rotation_speed);
+                }
+
+                // Acercar y alejar con 'Z' y 'X' o con la rueda del mouse;
+                // la velocidad depende de la distancia para que se sienta igual de cerca y de lejos
+                if window.is_key_down(Key::Z) {
+                    camera.zoom((-delta_time).exp());
+                }
+                if window.is_key_down(Key::X) {
+                    camera.zoom(delta_time.exp());
+                }
+                if let Some((_, scroll_y)) = window.get_scroll_wheel() {
+                    camera.zoom((-0.1 * scroll_y).exp());
+                }
+
+                // Desplazar la vista con las flechas
+                let radius = (camera.eye - camera.center).magnitude();
+                let pan_step = radius * 0.5 * delta_time;
+                if window.is_key_down(Key::Left) {
+                    camera.pan(-pan_step, 0.0);
+                }
+                if window.is_key_down(Key::Right) {
+                    camera.pan(pan_step, 0.0);
+                }
+                if window.is_key_down(Key::Up) {
+                    camera.pan(0.0, pan_step);
+                }
+                if window.is_key_down(Key::Down) {
+                    camera.pan(0.0, -pan_step);
+                }
+
+                // Encuadrar el bloque bajo el cursor (o en el centro de la pantalla) con 'C'
+                if window.is_key_pressed(Key::C, KeyRepeat::No) {
+                    let (ray_origin, ray_direction) = cursor_ray(&window, &camera);
+                    if let Some(cube) = objects.pick(&ray_origin, &ray_direction, environment.view_distance) {
+                        camera.frame(cube.center());
+                    }
+                }
+            }
+            CameraMode::Fly => {
+                let step = fly_speed * delta_time;
+                if window.is_key_down(Key::W) {
+                    camera.fly(step, 0.0, 0.0);
+                }
+                if window.is_key_down(Key::S) {
+                    camera.fly(-step, 0.0, 0.0);
+                }
+                if window.is_key_down(Key::D) {
+                    camera.fly(0.0, step, 0.0);
+                }
+                if window.is_key_down(Key::A) {
+                    camera.fly(0.0, -step, 0.0);
+                }
+                if window.is_key_down(Key::Space) {
+                    camera.fly(0.0, 0.0, step);
+                }
+                if window.is_key_down(Key::LeftShift) {
+                    camera.fly(0.0, 0.0, -step);
+                }
+
+                // Ajustar la velocidad de vuelo con '[' y ']'
+                if window.is_key_down(Key::RightBracket) {
+                    fly_speed = (fly_speed * (1.0 + delta_time)).min(50.0);
+                }
+                if window.is_key_down(Key::LeftBracket) {
+                    fly_speed = (fly_speed * (1.0 - delta_time)).max(0.2);
+                }
+            }
+        }
+
+        // Mirar alrededor arrastrando con el botón izquierdo del mouse en vuelo libre
+        let mouse = window.get_mouse_pos(MouseMode::Discard);
+        if camera_mode == CameraMode::Fly && window.get_mouse_down(MouseButton::Left) {
+            if let (Some((x, y)), Some((last_x, last_y))) = (mouse, last_mouse) {
+                camera.look((x - last_x) * mouse_sensitivity, -(y - last_y) * mouse_sensitivity);
+            }
+        }
+        last_mouse = mouse;
+
+        // Campo de visión con ',' y '.'; 'P' cambia entre perspectiva y ortográfica, 'I' pone la vista isométrica
+        if window.is_key_down(Key::Comma) {
+            camera.fov = (camera.fov - delta_time).max(10.0f32.to_radians());
+        }
+        if window.is_key_down(Key::Period) {
+            camera.fov = (camera.fov + delta_time).min(120.0f32.to_radians());
+        }
+        if window.is_key_pressed(Key::P, KeyRepeat::No) {
+            camera.toggle_projection();
+            if let Some(height) = config.view_height {
+                camera.set_view_height(height);
+            }
+        }
+        if window.is_key_pressed(Key::I, KeyRepeat::No) {
+            camera_mode = CameraMode::Orbit;
+            camera.isometric();
+            if let Some(height) = config.view_height {
+                camera.set_view_height(height);
+            }
+        }
+
+        // Profundidad de campo: 'N' y 'M' cierran y abren la lente; clic derecho enfoca el bloque bajo el cursor
+        if window.is_key_down(Key::N) {
+            camera.aperture = (camera.aperture - 0.2 * delta_time).max(0.0);
+        }
+        if window.is_key_down(Key::M) {
+            camera.aperture = (camera.aperture + 0.2 * delta_time).min(1.0);
+        }
+        if window.get_mouse_down(MouseButton::Right) {
+            let (ray_origin, ray_direction) = cursor_ray(&window, &camera);
+            let intersect = objects.closest_intersect(&ray_origin, &ray_direction, environment.view_distance);
+            if intersect.is_intersecting {
+                camera.focus_on(intersect.point);
+            }
+        }
+
+        // Avanzar el encuadre suave hacia el bloque elegido
+        camera.update(delta_time);
+
+        // Recorrido de cámara: 'K' agrega la vista actual dos segundos después del último keyframe,
+        // 'L' reproduce o detiene el recorrido y 'J' lo guarda
+        if window.is_key_pressed(Key::K, KeyRepeat::No) {
+            let time = camera_path.keyframes.last().map_or(0.0, |k| k.time + 2.0);
+            camera_path.push(&camera, time);
+            println!("Keyframe {} en {:.1} s", camera_path.keyframes.len(), time);
         }
-        if window.is_key_down(Key::D) {
-            camera.orbit(-adjusted_rotation_speed, 0.0);
+        if window.is_key_pressed(Key::L, KeyRepeat::No) {
+            playback = match playback {
+                Some(_) => None,
+                None if !camera_path.keyframes.is_empty() => Some(0.0),
+                None => {
+                    println!("El recorrido no tiene keyframes (agrega con 'K')");
+                    None
+                }
+            };
         }
-        if window.is_key_down(Key::W) {
-            camera.orbit(0.0, -adjusted_rotation_speed);
+        if window.is_key_pressed(Key::J, KeyRepeat::No) {
+            match camera_path.save(&path_file) {
+                Ok(()) => println!("Recorrido guardado en {}", path_file),
+                Err(e) => println!("{}", e),
+            }
         }
-        if window.is_key_down(Key::S) {
-            camera.orbit(0.0, adjusted_rotation_speed);
+        if let Some(time) = playback {
+            camera_path.apply(&mut camera, time);
+            playback = (time < camera_path.duration()).then_some(time + delta_time);
         }
 
         // Cambiar la intensidad con teclas '+' y '-'
@@ -109,171 +709,447 @@ fn main() {
             light.intensity = (light.intensity - 0.1).max(0.0);  // Evitar que sea negativa
         }
 
-        // Cambiar color con teclas numéricas
-        if window.is_key_down(Key::Key1) {  // Amarillo reconfortante
-            light.color = Color::new(255, 223, 128);
+        // Vistas guardadas: Ctrl + número guarda la vista actual, Alt + número la recupera
+        let ctrl = window.is_key_down(Key::LeftCtrl) || window.is_key_down(Key::RightCtrl);
+        let alt = window.is_key_down(Key::LeftAlt) || window.is_key_down(Key::RightAlt);
+        let number_keys = [Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5, Key::Key6, Key::Key7, Key::Key8, Key::Key9];
+        for (index, key) in number_keys.into_iter().enumerate() {
+            if !window.is_key_pressed(key, KeyRepeat::No) {
+                continue;
+            }
+            let slot = index + 1;
+            if ctrl {
+                bookmarks.store(slot, &camera);
+                match bookmarks.save(&bookmarks_file) {
+                    Ok(()) => println!("Vista {} guardada en {}", slot, bookmarks_file),
+                    Err(e) => println!("{}", e),
+                }
+            } else if alt && !bookmarks.recall(slot, &mut camera) {
+                println!("La vista {} está vacía (guárdala con Ctrl + {})", slot, slot);
+            }
+        }
+
+        // Cambiar color con teclas numéricas (sin modificadores, que se usan para las vistas)
+        if !ctrl && !alt {
+            if window.is_key_down(Key::Key1) {  // Amarillo reconfortante
+                light.color = Color::new(255, 223, 128);
+            }
+            if window.is_key_down(Key::Key2) {  // Azul de anochecer
+                light.color = Color::new(128, 128, 255);
+            }
+            if window.is_key_down(Key::Key3) {  // Verde brillante
+                light.color = Color::new(128, 255, 128);
+            }
+        }
+
+        // Estéreo: 'V' pasa por side-by-side, top-bottom, anaglifo y apagado; ';' y '\'' cambian la separación
+        // de los ojos. Las miradas convergen en la distancia de enfoque (clic derecho)
+        if window.is_key_pressed(Key::V, KeyRepeat::No) {
+            stereo = match stereo.map(|params| params.layout) {
+                None => Some(StereoParams::new(StereoLayout::SideBySide, camera.focus_distance)),
+                Some(StereoLayout::SideBySide) => stereo.map(|params| StereoParams { layout: StereoLayout::TopBottom, ..params }),
+                Some(StereoLayout::TopBottom) => stereo.map(|params| StereoParams { layout: StereoLayout::Anaglyph, ..params }),
+                Some(StereoLayout::Anaglyph) => None,
+            };
         }
-        if window.is_key_down(Key::Key2) {  // Azul de anochecer
-            light.color = Color::new(128, 128, 255);
+        if let Some(params) = &mut stereo {
+            if window.is_key_down(Key::Semicolon) {
+                params.eye_separation = (params.eye_separation - 0.2 * delta_time).max(0.0);
+            }
+            if window.is_key_down(Key::Apostrophe) {
+                params.eye_separation = (params.eye_separation + 0.2 * delta_time).min(2.0);
+            }
+            params.convergence = camera.focus_distance;
+        }
+
+        // 'Tab' muestra las pasadas una por una (profundidad, normales, albedo, material, cubo, sombra)
+        if window.is_key_pressed(Key::Tab, KeyRepeat::No) {
+            let next = match shown_aov {
+                None => 0,
+                Some(aov) => Aov::ALL.iter().position(|&a| a == aov).unwrap() + 1,
+            };
+            shown_aov = Aov::ALL.get(next).copied();
+            println!("Mostrando: {}", shown_aov.map_or("imagen final", |aov| aov.name()));
         }
-        if window.is_key_down(Key::Key3) {  // Verde brillante
-            light.color = Color::new(128, 255, 128);
+
+        // 'G' activa el antialiasing adaptativo: 3 x 3 rayos al azar con filtro triangular, solo en los bordes
+        if window.is_key_pressed(Key::G, KeyRepeat::No) {
+            antialiasing = match antialiasing {
+                Some(_) => None,
+                None => Some(Sampling { grid: 3, pattern: Pattern::Jittered, filter: Filter::Tent, adaptive: Some(0.1) }),
+            };
+            println!("Antialiasing {}", if antialiasing.is_some() { "activado" } else { "desactivado" });
+        }
+
+        // Tamaño del framebuffer según la escala actual
+        let render_start = Instant::now();
+        let (framebuffer_width, framebuffer_height) = resolution.framebuffer_size(window_width, window_height);
+        if (framebuffer.width, framebuffer.height) != (framebuffer_width, framebuffer_height) {
+            framebuffer = Framebuffer::new(framebuffer_width, framebuffer_height);
         }
 
         framebuffer.clear();  // Limpiar el framebuffer reducido antes de renderizar
     
         // Renderizamos en el framebuffer reducido
-        render(&mut framebuffer, &objects, &camera, &light);
+        match (&stereo, shown_aov) {
+            (_, Some(aov)) => AovBuffers::render(&objects, &camera, &light, &environment, framebuffer.width, framebuffer.height)
+                .to_framebuffer(aov, &mut framebuffer),
+            // Con la ventana casi cerrada no hay lugar para los dos ojos: se ve uno solo
+            (Some(params), None) => render_stereo(&mut framebuffer, &objects, &camera, &light, &environment, params)
+                .unwrap_or_else(|_| render(&mut framebuffer, &objects, &camera, &light, &environment)),
+            (None, None) => match &antialiasing {
+                Some(sampling) => render_supersampled(&mut framebuffer, &objects, &camera, &light, &environment, sampling),
+                None => render(&mut framebuffer, &objects, &camera, &light, &environment),
+            },
+        }
+
+        // 'U' cambia el filtro de escalado y 'Y' activa o desactiva el realce de bordes
+        if window.is_key_pressed(Key::U, KeyRepeat::No) {
+            upscale_filter = upscale_filter.next();
+            println!("Escalado: {}", upscale_filter.name());
+        }
+        if window.is_key_pressed(Key::Y, KeyRepeat::No) {
+            sharpness = if sharpness > 0.0 { 0.0 } else { 0.5 };
+            println!("Realce de bordes {}", if sharpness > 0.0 { "activado" } else { "desactivado" });
+        }
 
         // Escalar el framebuffer reducido al tamaño completo de la ventana
-        let scaled_buffer = upscale_framebuffer(&framebuffer, window_width, window_height);
+        let mut scaled_buffer = upscale_framebuffer(&framebuffer, window_width, window_height, upscale_filter);
+        sharpen(&mut scaled_buffer, window_width, window_height, sharpness);
+
+        // La cámara se mueve si cambió algo de la vista desde el cuadro anterior: entonces se baja la
+        // resolución para llegar al tiempo objetivo; quieta, se refina hasta la escala máxima
+        let pose = Some((camera.eye, camera.center, camera.up, camera.fov, camera.aperture));
+        resolution.update(render_start.elapsed().as_secs_f32(), pose != last_pose);
+        last_pose = pose;
+
+        // Capturas con 'F12': a la resolución del framebuffer, con Shift a la de la ventana y con Ctrl
+        // se vuelve a renderizar la vista al doble de la ventana con 4 rayos por píxel
+        if window.is_key_pressed(Key::F12, KeyRepeat::No) {
+            let shift = window.is_key_down(Key::LeftShift) || window.is_key_down(Key::RightShift);
+            let result = if ctrl {
+                let path = screenshot_path("_hq");
+                println!("Renderizando captura a {}x{}...", window_width * 2, window_height * 2);
+                let mut high_res = Framebuffer::new(window_width * 2, window_height * 2);
+                render_supersampled(&mut high_res, &objects, &camera, &light, &environment, &Sampling::new(4));
+                high_res.save(&path).map(|_| path).map_err(|e| e.to_string())
+            } else if shift {
+                let path = screenshot_path("");
+                save_buffer(&scaled_buffer, window_width, window_height, &path).map(|_| path)
+            } else {
+                let path = screenshot_path("");
+                framebuffer.save(&path).map(|_| path).map_err(|e| e.to_string())
+            };
+            match result {
+                Ok(path) => println!("Captura guardada en {}", path),
+                Err(e) => println!("No se pudo guardar la captura: {}", e),
+            }
+        }
     
+        // 'H' muestra u oculta el HUD; se dibuja sobre la imagen ya escalada para que el texto se vea nítido
+        // y no sale en las capturas
+        if window.is_key_pressed(Key::H, KeyRepeat::No) {
+            show_hud = !show_hud;
+        }
+        let mut screen = Framebuffer::from_buffer(window_width, window_height, scaled_buffer);
+        if show_hud {
+            let mut rays = match &antialiasing {
+                Some(sampling) => format!("hasta {}x{} adaptativo", sampling.grid, sampling.grid),
+                None => "1".to_string(),
+            };
+            if camera.aperture > 0.0 {
+                rays = format!("{} x {} de lente", rays, constants::DOF_SAMPLES);
+            }
+            let view = match (shown_aov, &stereo) {
+                (Some(aov), _) => format!("pasada {}", aov.name()),
+                (None, Some(params)) => format!("estereo {:?}", params.layout),
+                (None, None) => "imagen final".to_string(),
+            };
+            let projection = match camera.projection {
+                Projection::Perspective => "perspectiva",
+                Projection::Orthographic { .. } => "ortografica",
+                Projection::Equirectangular => "equirectangular",
+            };
+            let mode = if camera_mode == CameraMode::Orbit { "orbita" } else { "vuelo" };
+            let lines = [
+                format!("FPS {:.0} ({:.1} ms)", 1.0 / average_frame_time.max(1e-4), average_frame_time * 1000.0),
+                format!("Resolucion {}x{} ({:.0}%) {}", framebuffer.width, framebuffer.height,
+                    100.0 * framebuffer.width as f32 / window_width as f32, upscale_filter.name()),
+                format!("Rayos por pixel: {}", rays),
+                format!("Vista: {}", view),
+                format!("Camara: {} {} fov {:.0}", mode, projection, camera.fov.to_degrees()),
+                format!("Ojo: {:.2}, {:.2}, {:.2}", camera.eye.x, camera.eye.y, camera.eye.z),
+                format!("Centro: {:.2}, {:.2}, {:.2}", camera.center.x, camera.center.y, camera.center.z),
+                format!("Luz: intensidad {:.1} color {},{},{}", light.intensity, light.color.r, light.color.g, light.color.b),
+            ];
+            draw_panel(&mut screen, 0, 0, &lines, 2);
+        }
+
+        // Bloque bajo el cursor: se marca con un contorno y abajo a la izquierda se muestra su índice, posición,
+        // material y la cara apuntada. 'O' lo muestra u oculta; en estéreo no se usa porque hay dos vistas
+        if window.is_key_pressed(Key::O, KeyRepeat::No) {
+            show_picking = !show_picking;
+        }
+        if show_picking && stereo.is_none() && window.get_mouse_pos(MouseMode::Discard).is_some() {
+            let (ray_origin, ray_direction) = cursor_ray(&window, &camera);
+            let intersect = objects.closest_intersect(&ray_origin, &ray_direction, environment.view_distance);
+            let picked = objects.pick(&ray_origin, &ray_direction, environment.view_distance);
+            if let (true, Some(cube)) = (intersect.is_intersecting, picked) {
+                draw_outline(&mut screen, &camera, &cube, 0xFFFF00);
+
+                // En los mundos por chunks no hay lista de cubos: el bloque se identifica por su celda
+                let block = match &objects {
+                    Geometry::Cubes(_) => format!("Bloque #{}", intersect.object),
+                    Geometry::Chunks(world) => {
+                        let cell = cube.min / world.block_size;
+                        format!("Celda {:.0}, {:.0}, {:.0}", cell.x, cell.y, cell.z)
+                    }
+                };
+                let normal = intersect.normal + Vec3::zeros();  // Sumar cero quita los -0
+                let lines = [
+                    format!("{}: {}", block, intersect.material.name),
+                    format!("Min: {:.2}, {:.2}, {:.2}", cube.min.x, cube.min.y, cube.min.z),
+                    format!("Max: {:.2}, {:.2}, {:.2}", cube.max.x, cube.max.y, cube.max.z),
+                    format!("Cara: {:.0}, {:.0}, {:.0} a {:.2} de distancia", normal.x, normal.y, normal.z, intersect.distance),
+                ];
+                let (_, panel_height) = panel_size(&lines, 2);
+                draw_panel(&mut screen, 0, window_height.saturating_sub(panel_height), &lines, 2);
+            }
+        }
+
         // Actualizar la ventana con el buffer escalado
-        if let Err(e) = window.update_with_buffer(&scaled_buffer, window_width, window_height) {
+        if let Err(e) = window.update_with_buffer(&screen.buffer, window_width, window_height) {
             println!("Error al actualizar el buffer: {:?}", e);
         }
     
-        std::thread::sleep(Duration::from_millis(16));  // Aproximadamente 60 FPS
+        // Como mucho unos 60 FPS: dormimos lo que falte del cuadro
+        if let Some(rest) = std::time::Duration::from_millis(16).checked_sub(current_time.elapsed()) {
+            std::thread::sleep(rest);
+        }
     }
 
-    // Detener la música cuando el ciclo de la ventana se detiene
-    sink.stop();
+    // Detener la música cuando el ciclo de la ventana se detiene (el sink la corta al soltarse)
+    #[cfg(feature = "audio")]
+    drop(music);
+    Ok(())
+}
+
+// Reproduce la música en bucle; hay que conservar el stream mientras suene
+#[cfg(feature = "audio")]
+fn start_music(path: &str) -> Option<(OutputStream, Sink)> {
+    let (stream, stream_handle) = match OutputStream::try_default() {
+        Ok(output) => output,
+        Err(e) => {
+            println!("Sin música: no hay dispositivo de audio ({})", e);
+            return None;
+        }
+    };
+    let sink = Sink::try_new(&stream_handle).ok()?;
+
+    // Cargar el archivo de música
+    let source = match std::fs::File::open(path).map_err(|e| e.to_string())
+        .and_then(|file| Decoder::new(std::io::BufReader::new(file)).map_err(|e| e.to_string())) {
+        Ok(source) => source,
+        Err(e) => {
+            println!("Sin música: no se pudo cargar {} ({})", path, e);
+            return None;
+        }
+    };
+
+    // Hacer que el archivo de música se repita indefinidamente
+    sink.append(source.repeat_infinite());
+
+    // Controlar el volumen
+    sink.set_volume(1.5);
+    Some((stream, sink))
 }
 
 fn reflect(incident: &Vec3, normal: &Vec3) -> Vec3 {
     incident - 2.0 * incident.dot(normal) * normal
 }
 
-pub fn cast_ray(ray_origin: &Vec3, ray_direction: &Vec3, objects: &[Cube], light: &Light, distance: f32) -> Color {
-    let mut intersect = Intersect::empty();
-    let mut zbuffer = f32::INFINITY;
-    let mut hit_cube: Option<&Cube> = None;
+// Rayo primario que pasa por el cursor, o por el centro de la ventana si el mouse está afuera
+#[cfg(feature = "viewer")]
+fn cursor_ray(window: &Window, camera: &Camera) -> (Vec3, Vec3) {
+    let (width, height) = window.get_size();
+    let (x, y) = window.get_mouse_pos(MouseMode::Discard)
+        .unwrap_or((width as f32 * 0.5, height as f32 * 0.5));
+    camera.primary_ray(x, y, width as f32, height as f32)
+}
 
-    // Primer paso: Encontrar el cubo más cercano
-    for object in objects {
-        // Verificamos si el objeto está cerca de la cámara antes de calcular intersecciones
-        let distance_to_camera = (object.min - *ray_origin).magnitude();
-        if distance_to_camera > distance {  // Omitimos objetos que están demasiado lejos
-            continue;
-        }
+// Resultado de iluminar el punto que alcanza un rayo, antes de combinarlo en un color
+struct Shading {
+    base_color: Color,       // Color de la textura o difuso del material
+    in_shadow: bool,
+    diffuse_intensity: f32,
+    ambient_intensity: f32,
+    specular_intensity: f32,
+    material: Material,
+    distance: f32,           // Distancia del origen del rayo al punto alcanzado
+    normal: Vec3,
+    uv: [f32; 2],
+    object: usize,           // Índice del cubo alcanzado
+}
 
-        let tmp = object.ray_intersect(ray_origin, ray_direction);
-        if tmp.is_intersecting && tmp.distance < zbuffer {
-            zbuffer = tmp.distance;
-            intersect = tmp;
-            hit_cube = Some(object);
-        }
-    }
+fn shade<S: SceneIntersect + ?Sized>(ray_origin: &Vec3, ray_direction: &Vec3, objects: &S, light: &Light, environment: &Environment) -> Option<Shading> {
+    // Primer paso: Encontrar el cubo más cercano
+    let intersect = objects.closest_intersect(ray_origin, ray_direction, environment.view_distance);
 
-    // Si no hay intersección, devolvemos el color de fondo
+    // Si no hay intersección, no hay nada que iluminar
     if !intersect.is_intersecting {
-        return Color::new(64, 128, 96);  // Fondo celeste o el color de fondo deseado
+        return None;
     }
+    Some(shade_hit(intersect, ray_origin, objects, light, environment))
+}
 
+// Ilumina un punto ya encontrado; las pasadas de depuración lo usan para quedarse también con `Intersect`
+fn shade_hit<S: SceneIntersect + ?Sized>(intersect: Intersect, ray_origin: &Vec3, objects: &S, light: &Light, environment: &Environment) -> Shading {
     // Si hay intersección, lanzamos un rayo de sombra desde el punto de intersección hacia la luz
-    let shadow_ray_origin = intersect.point;
+    // Despegamos el origen de la superficie para que el error de redondeo no lo deje dentro del cubo
+    let shadow_ray_origin = intersect.point + intersect.normal * 1e-4;
     let shadow_ray_direction = (light.position - shadow_ray_origin).normalize();
     let light_distance = (light.position - shadow_ray_origin).magnitude();
 
-    // Bandera para determinar si el punto está en sombra
-    let mut in_shadow = false;
-
-    for object in objects {
-        if let Some(cube) = hit_cube {
-            if cube != object {  // Nos aseguramos de no comparar el cubo con sí mismo
-                let shadow_intersection = object.ray_intersect(&shadow_ray_origin, &shadow_ray_direction);
-                if shadow_intersection.is_intersecting && shadow_intersection.distance < light_distance {
-                    in_shadow = true;  // Si encontramos una intersección, el punto está en sombra
-                    break;
-                }
-            }
-        }
-    }
+    // Las caras que no miran a la luz siempre están en sombra, así el resultado no depende
+    // de cómo se dividan los bloques; el resto lo decide el rayo de sombra
+    let in_shadow = intersect.normal.dot(&shadow_ray_direction) <= 0.0
+        || objects.occluded(&shadow_ray_origin, &shadow_ray_direction, light_distance);
 
     // Dirección de la luz hacia el punto de intersección
     let light_dir = (light.position - intersect.point).normalize();
 
     // Intensidad difusa basada en el ángulo entre la normal y la dirección de la luz
-    let diffuse_intensity = intersect.normal.dot(&light_dir).max(0.0).min(1.0);
+    let diffuse_intensity = intersect.normal.dot(&light_dir).clamp(0.0, 1.0);
+
+    // Las coordenadas UV ya vienen calculadas en la intersección; las texturas animadas se desplazan con el tiempo
+    let uv = scroll_uv(intersect.uv, intersect.material.uv_scroll, environment.time);
 
     // Color base (ya sea de la textura o el color difuso del material)
     let base_color = if let Some(texture) = &intersect.material.texture {
-        // Calcular las coordenadas UV basadas en el cubo y la intersección
-        let uv = calculate_uv(intersect.point, intersect.normal, hit_cube.unwrap().min, hit_cube.unwrap().max);
         get_texture_color(texture, intersect.material.texture_width, intersect.material.texture_height, uv)
     } else {
         intersect.material.diffuse.unwrap_or(Color::new(255, 255, 255))  // Si no hay textura, usa el color difuso
     };
 
     // Luz ambiental ajustada
-    let ambient_intensity = 0.5;  // Menor luz ambiente
-
-    // Si está en sombra, solo aplicamos la luz ambiente
-    if in_shadow {
-        return base_color * ambient_intensity;
-    }
+    let ambient_intensity = environment.ambient;
 
     // Cálculo de reflect (iluminación especular)
     let view_dir = (ray_origin - intersect.point).normalize();
     let reflect_dir = reflect(&-light_dir, &intersect.normal);
     let specular_intensity = view_dir.dot(&reflect_dir).max(0.0).powf(intersect.material.specular);
-    let specular = light.color * intersect.material.albedo[1] * specular_intensity * light.intensity;
 
-    // Combinamos luz difusa, ambiente y reflect (especular)
-    let diffuse = base_color * intersect.material.albedo[0] * (diffuse_intensity + ambient_intensity);
-    let final_color = diffuse + specular;
+    Shading {
+        base_color,
+        in_shadow,
+        diffuse_intensity,
+        ambient_intensity,
+        specular_intensity,
+        material: intersect.material,
+        distance: intersect.distance,
+        normal: intersect.normal,
+        uv,
+        object: intersect.object,
+    }
+}
+
+pub fn cast_ray<S: SceneIntersect + ?Sized>(ray_origin: &Vec3, ray_direction: &Vec3, objects: &S, light: &Light, environment: &Environment) -> Color {
+    // Si no hay intersección, devolvemos el color de fondo
+    let Some(shading) = shade(ray_origin, ray_direction, objects, light, environment) else {
+        return environment.background;
+    };
+
+    // Si está en sombra, solo aplicamos la luz ambiente
+    if shading.in_shadow {
+        return shading.base_color * shading.ambient_intensity;
+    }
 
-    final_color
+    let specular = light.color * shading.material.albedo[1] * shading.specular_intensity * light.intensity;
 
+    // Combinamos luz difusa, ambiente y reflect (especular)
+    let diffuse = shading.base_color * shading.material.albedo[0] * (shading.diffuse_intensity + shading.ambient_intensity);
+    diffuse + specular
 }
 
-pub fn render(framebuffer: &mut Framebuffer, objects: &[Cube], camera: &Camera, light: &Light) {
-    let width = framebuffer.width as f32;
-    let height = framebuffer.height as f32;
-    let aspect_ratio = width / height;
-    let fov = std::f32::consts::PI / 4.0;
-    let perspective_scale = (fov * 0.5).tan();
+// Igual que `cast_ray`, pero devuelve radiancia RGB lineal sin recortar. Además suma la emisión
+// de los materiales (las lámparas), que en la salida de 8 bits se quemaría a un color plano
+pub fn cast_ray_hdr<S: SceneIntersect + ?Sized>(ray_origin: &Vec3, ray_direction: &Vec3, objects: &S, light: &Light, environment: &Environment) -> Vec3 {
+    let Some(shading) = shade(ray_origin, ray_direction, objects, light, environment) else {
+        return environment.background.to_linear();
+    };
 
-    // Paralelizar el cálculo por filas usando `par_iter_mut`
-    framebuffer.buffer.par_chunks_mut(framebuffer.width).enumerate().for_each(|(y, row)| {
-        for x in 0..framebuffer.width {
-            let screen_x = (2.0 * x as f32) / width - 1.0;
-            let screen_y = -(2.0 * y as f32) / height + 1.0;
+    let base = shading.base_color.to_linear();
+    let emission = shading.material.emissive.map_or(Vec3::zeros(), |color| color.to_linear() * shading.material.emission_intensity);
 
-            let screen_x = screen_x * aspect_ratio * perspective_scale;
-            let screen_y = screen_y * perspective_scale;
+    if shading.in_shadow {
+        return base * shading.ambient_intensity + emission;
+    }
 
-            let ray_direction = nalgebra_glm::normalize(&Vec3::new(screen_x, screen_y, -1.0));
-            let rotated_direction = camera.base_change(&ray_direction);
+    let specular = light.color.to_linear() * (shading.material.albedo[1] * shading.specular_intensity * light.intensity);
+    let diffuse = base * (shading.material.albedo[0] * (shading.diffuse_intensity + shading.ambient_intensity));
+    diffuse + specular + emission
+}
 
-            // Lanzamos rayos hacia los cubos y calculamos el color
-            let distance = 20.0; // O cualquier valor que haga sentido en tu lógica
-            let pixel_color = cast_ray(&camera.eye, &rotated_direction, objects, light, distance);
+pub fn render<S: SceneIntersect + Sync + ?Sized>(framebuffer: &mut Framebuffer, objects: &S, camera: &Camera, light: &Light, environment: &Environment) {
+    render_supersampled(framebuffer, objects, camera, light, environment, &Sampling::new(1));
+}
 
+// Renderiza con el antialiasing de `sampling`: varios rayos por píxel combinados con un filtro
+// para suavizar los bordes de los bloques y las costuras de las texturas
+pub fn render_supersampled<S: SceneIntersect + Sync + ?Sized>(framebuffer: &mut Framebuffer, objects: &S, camera: &Camera, light: &Light, environment: &Environment, sampling: &Sampling) {
+    // Los colores se combinan en 0..255 y se redondean al final
+    let image = render_samples(camera, framebuffer.width, framebuffer.height, sampling, 1.0 / 255.0, |origin, direction| {
+        let color = cast_ray(origin, direction, objects, light, environment);
+        Vec3::new(color.r as f32, color.g as f32, color.b as f32)
+    });
 
-            // Establecemos el color en el framebuffer
-            row[x] = pixel_color.to_hex();
-        }
+    framebuffer.buffer.par_iter_mut().zip(image).for_each(|(pixel, value)| {
+        let channel = |v: f32| v.round().clamp(0.0, 255.0) as u8;
+        *pixel = Color::new(channel(value.x), channel(value.y), channel(value.z)).to_hex();
     });
 }
 
-/// Función para escalar el framebuffer reducido al tamaño de la ventana completa
-pub fn upscale_framebuffer(framebuffer: &Framebuffer, target_width: usize, target_height: usize) -> Vec<u32> {
-    let mut scaled_buffer = vec![0; target_width * target_height];
-    let x_ratio = framebuffer.width as f32 / target_width as f32;
-    let y_ratio = framebuffer.height as f32 / target_height as f32;
+// Igual que `render_supersampled`, pero guarda radiancia lineal sin recortar para la salida HDR
+pub fn render_hdr<S: SceneIntersect + Sync + ?Sized>(framebuffer: &mut HdrFramebuffer, objects: &S, camera: &Camera, light: &Light, environment: &Environment, sampling: &Sampling) {
+    framebuffer.buffer = render_samples(camera, framebuffer.width, framebuffer.height, sampling, 1.0, |origin, direction| {
+        cast_ray_hdr(origin, direction, objects, light, environment)
+    });
+}
 
-    for y in 0..target_height {
-        for x in 0..target_width {
-            let src_x = (x as f32 * x_ratio).floor() as usize;
-            let src_y = (y as f32 * y_ratio).floor() as usize;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::Cube;
+    use crate::material::Material;
 
-            let src_index = src_y * framebuffer.width + src_x;
-            let dest_index = y * target_width + x;
+    fn white_cube(min: Vec3, max: Vec3) -> Cube {
+        let material = Material::new("blanco", Some(Color::new(200, 200, 200)), None, 0, 0, 1.0, [1.0, 0.0], None, 0.0);
+        Cube { min, max, material, uv_tile: None }
+    }
 
-            scaled_buffer[dest_index] = framebuffer.buffer[src_index];
-        }
+    #[test]
+    fn face_turned_away_from_the_light_gets_only_ambient() {
+        let objects = [white_cube(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0))];
+        // Luz debajo del cubo y rayo que cae sobre la cara de arriba
+        let light = Light::new(Vec3::new(0.5, -5.0, 0.5), Color::new(255, 255, 255), 1.0);
+        let color = cast_ray(&Vec3::new(0.5, 3.0, 0.5), &Vec3::new(0.0, -1.0, 0.0), &objects[..], &light, &Environment::default());
+        assert_eq!(color, Color::new(200, 200, 200) * 0.5);
     }
 
-    scaled_buffer
-}
\ No newline at end of file
+    #[test]
+    fn face_towards_the_light_is_lit() {
+        let objects = [white_cube(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0))];
+        let light = Light::new(Vec3::new(0.5, 5.0, 0.5), Color::new(255, 255, 255), 1.0);
+        let color = cast_ray(&Vec3::new(0.5, 3.0, 0.5), &Vec3::new(0.0, -1.0, 0.0), &objects[..], &light, &Environment::default());
+        assert_eq!(color, Color::new(200, 200, 200) * 1.5);
+    }
+
+    #[test]
+    fn large_boxes_near_the_camera_are_not_culled() {
+        // La esquina mínima queda a más de 20 unidades, pero la superficie está a 2
+        let objects = [white_cube(Vec3::new(-50.0, -1.0, -50.0), Vec3::new(50.0, 0.0, 50.0))];
+        let light = Light::new(Vec3::new(0.0, 5.0, 0.0), Color::new(255, 255, 255), 1.0);
+        let color = cast_ray(&Vec3::new(0.0, 2.0, 0.0), &Vec3::new(0.0, -1.0, 0.0), &objects[..], &light, &Environment::default());
+        assert_ne!(color, Environment::default().background);
+    }
+}
@@ -10,11 +10,14 @@ pub struct Material {
     pub albedo: [f32; 2],         // Factores de reflectancia
     pub emissive: Option<Color>,  // Color emisivo (si el material emite luz)
     pub emission_intensity: f32,  // Intensidad de emisión de luz
+    pub uv_scroll: [f32; 2],      // Texturas por segundo que se desplaza la textura en U y V (textura animada)
+    pub name: &'static str,       // Nombre del bloque (para mostrarlo y para identificar el material)
 }
 
 
 impl Material {
-    pub fn new(diffuse: Option<Color>, texture: Option<Vec<u8>>, texture_width: u32, texture_height: u32, specular: f32, albedo: [f32; 2], emissive: Option<Color>, emission_intensity: f32) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(name: &'static str, diffuse: Option<Color>, texture: Option<Vec<u8>>, texture_width: u32, texture_height: u32, specular: f32, albedo: [f32; 2], emissive: Option<Color>, emission_intensity: f32) -> Self {
         Material {
             diffuse,
             texture,
@@ -24,9 +27,16 @@ impl Material {
             albedo,
             emissive,
             emission_intensity,
+            uv_scroll: [0.0, 0.0],
+            name,
         }
     }
 
+    // Identificador estable del material (hash FNV-1a del nombre), igual entre ejecuciones
+    pub fn id(&self) -> u32 {
+        self.name.bytes().fold(0x811c9dc5u32, |hash, byte| (hash ^ byte as u32).wrapping_mul(0x01000193))
+    }
+
     pub fn default() -> Self {
         Material {
             diffuse: Some(Color::new(50, 50, 50)),  // Un color gris oscuro
@@ -37,6 +47,8 @@ impl Material {
             albedo: [0.0, 0.0],  // Ninguna reflectancia
             emissive: None,
             emission_intensity: 0.0,
+            uv_scroll: [0.0, 0.0],
+            name: "default",
         }
     }
 
@@ -50,6 +62,8 @@ impl Material {
             albedo: [0.8, 0.1],
             emissive: None,  // Más luz difusa, menos especular
             emission_intensity: 0.0,
+            uv_scroll: [0.0, 0.0],
+            name: "grass",
         }
     }
     
@@ -63,6 +77,8 @@ impl Material {
             albedo: [0.9, 0.05], // Alta luz difusa, casi nada de especular
             emissive: None,
             emission_intensity: 0.0,
+            uv_scroll: [0.0, 0.0],
+            name: "dirt",
         }
     }
     
@@ -76,6 +92,8 @@ impl Material {
             albedo: [0.7, 0.1],  // Principalmente luz difusa
             emissive: None,
             emission_intensity: 0.0,
+            uv_scroll: [0.0, 0.0],
+            name: "stone",
         }
     }
     
@@ -89,6 +107,8 @@ impl Material {
             albedo: [0.6, 0.4],  // Más especular que difuso
             emissive: None,
             emission_intensity: 0.0,
+            uv_scroll: [0.0, 0.0],
+            name: "diamond",
         }
     }
     
@@ -102,6 +122,8 @@ impl Material {
             albedo: [0.8, 0.1],  // Principalmente difuso
             emissive: None,
             emission_intensity: 0.0,
+            uv_scroll: [0.0, 0.0],
+            name: "azalea",
         }
     }
     
@@ -115,6 +137,8 @@ impl Material {
             albedo: [0.75, 0.1], // Más difuso, poco especular
             emissive: None,
             emission_intensity: 0.0,
+            uv_scroll: [0.0, 0.0],
+            name: "bamboo",
         }
     }
     
@@ -128,6 +152,8 @@ impl Material {
             albedo: [0.9, 0.05], // Muy poco especular, casi todo difuso
             emissive: None,
             emission_intensity: 0.0,
+            uv_scroll: [0.0, 0.0],
+            name: "sand",
         }
     }
     
@@ -141,6 +167,8 @@ impl Material {
             albedo: [0.8, 0.1],  // Luz difusa
             emissive: None,
             emission_intensity: 0.0,
+            uv_scroll: [0.0, 0.0],
+            name: "moosy",
         }
     }
     
@@ -154,6 +182,8 @@ impl Material {
             albedo: [0.7, 0.2],  // Un poco de especularidad
             emissive: None,
             emission_intensity: 0.0,
+            uv_scroll: [0.0, 0.0],
+            name: "moosy_block",
         }
     }
     
@@ -167,6 +197,8 @@ impl Material {
             albedo: [0.4, 0.6],  // Más luz especular, menos difusa
             emissive: Some(Color::new(255, 215, 0)), 
             emission_intensity: 10.0,
+            uv_scroll: [0.0, 0.25],  // La textura de la lámpara se desplaza despacio en las animaciones
+            name: "lamp",
         }
     }
     
@@ -180,6 +212,8 @@ impl Material {
             albedo: [0.5, 0.5],  // Equilibrio entre difuso y especular
             emissive: None,
             emission_intensity: 0.0,
+            uv_scroll: [0.0, 0.0],
+            name: "emerald",
         }
     }    
 
@@ -194,6 +228,8 @@ impl Material {
             albedo: [0.8, 0.1],  // Equilibrio entre difuso y especular
             emissive: None,
             emission_intensity: 0.0,
+            uv_scroll: [0.0, 0.0],
+            name: "concrete",
         }
     } 
 
@@ -208,6 +244,8 @@ impl Material {
             albedo: [0.4, 0.6],
             emissive: None,
             emission_intensity: 0.0,
+            uv_scroll: [0.0, 0.0],
+            name: "glass",
         }
     }
 
@@ -221,7 +259,70 @@ impl Material {
             albedo: [0.8, 0.1],
             emissive: None,  // Más luz difusa, menos especular
             emission_intensity: 0.0,
+            uv_scroll: [0.0, 0.0],
+            name: "terracota",
         }
     } 
+
+    // Materiales sin textura para los árboles generados (solo color difuso)
+    pub fn oak_log() -> Self {
+        Material {
+            diffuse: Some(Color::new(102, 81, 51)),
+            texture: None,
+            texture_width: 0,
+            texture_height: 0,
+            specular: 10.0,      // Madera casi mate
+            albedo: [0.85, 0.05],
+            emissive: None,
+            emission_intensity: 0.0,
+            uv_scroll: [0.0, 0.0],
+            name: "oak_log",
+        }
+    }
+
+    pub fn oak_leaves() -> Self {
+        Material {
+            diffuse: Some(Color::new(60, 120, 40)),
+            texture: None,
+            texture_width: 0,
+            texture_height: 0,
+            specular: 15.0,      // Hojas pueden tener algo de brillo
+            albedo: [0.8, 0.1],
+            emissive: None,
+            emission_intensity: 0.0,
+            uv_scroll: [0.0, 0.0],
+            name: "oak_leaves",
+        }
+    }
+
+    pub fn birch_log() -> Self {
+        Material {
+            diffuse: Some(Color::new(215, 210, 195)),
+            texture: None,
+            texture_width: 0,
+            texture_height: 0,
+            specular: 10.0,      // Corteza clara y mate
+            albedo: [0.85, 0.05],
+            emissive: None,
+            emission_intensity: 0.0,
+            uv_scroll: [0.0, 0.0],
+            name: "birch_log",
+        }
+    }
+
+    pub fn birch_leaves() -> Self {
+        Material {
+            diffuse: Some(Color::new(110, 150, 60)),
+            texture: None,
+            texture_width: 0,
+            texture_height: 0,
+            specular: 15.0,
+            albedo: [0.8, 0.1],
+            emissive: None,
+            emission_intensity: 0.0,
+            uv_scroll: [0.0, 0.0],
+            name: "birch_leaves",
+        }
+    }
 }
 
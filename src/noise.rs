@@ -0,0 +1,88 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+// Ruido de Perlin clásico (versión "mejorada" de Ken Perlin) con tabla de permutaciones sembrada
+pub struct Perlin {
+    perm: [u8; 512],
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut table: Vec<u8> = (0..=255).collect();
+        table.shuffle(&mut rng);
+
+        // Duplicamos la tabla para no tener que envolver los índices
+        let mut perm = [0u8; 512];
+        for (i, value) in perm.iter_mut().enumerate() {
+            *value = table[i % 256];
+        }
+
+        Perlin { perm }
+    }
+
+    // Ruido 3D en el rango aproximado [-1, 1]
+    pub fn noise3(&self, x: f32, y: f32, z: f32) -> f32 {
+        let xi = (x.floor() as i32 & 255) as usize;
+        let yi = (y.floor() as i32 & 255) as usize;
+        let zi = (z.floor() as i32 & 255) as usize;
+
+        let xf = x - x.floor();
+        let yf = y - y.floor();
+        let zf = z - z.floor();
+
+        let u = fade(xf);
+        let v = fade(yf);
+        let w = fade(zf);
+
+        let p = &self.perm;
+        let a = p[xi] as usize + yi;
+        let aa = p[a] as usize + zi;
+        let ab = p[a + 1] as usize + zi;
+        let b = p[xi + 1] as usize + yi;
+        let ba = p[b] as usize + zi;
+        let bb = p[b + 1] as usize + zi;
+
+        lerp(w,
+            lerp(v,
+                lerp(u, grad(p[aa], xf, yf, zf), grad(p[ba], xf - 1.0, yf, zf)),
+                lerp(u, grad(p[ab], xf, yf - 1.0, zf), grad(p[bb], xf - 1.0, yf - 1.0, zf))),
+            lerp(v,
+                lerp(u, grad(p[aa + 1], xf, yf, zf - 1.0), grad(p[ba + 1], xf - 1.0, yf, zf - 1.0)),
+                lerp(u, grad(p[ab + 1], xf, yf - 1.0, zf - 1.0), grad(p[bb + 1], xf - 1.0, yf - 1.0, zf - 1.0))))
+    }
+
+    // Suma de varias octavas de ruido 2D (fBm), normalizada a [-1, 1]
+    pub fn fbm2(&self, x: f32, z: f32, octaves: u32) -> f32 {
+        let mut total = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+        let mut max_value = 0.0;
+
+        for _ in 0..octaves {
+            total += self.noise3(x * frequency, 0.5, z * frequency) * amplitude;
+            max_value += amplitude;
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+
+        total / max_value
+    }
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f32, a: f32, b: f32) -> f32 {
+    a + t * (b - a)
+}
+
+// Producto punto con uno de los 12 gradientes elegido por el hash
+fn grad(hash: u8, x: f32, y: f32, z: f32) -> f32 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 { y } else if h == 12 || h == 14 { x } else { z };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
@@ -0,0 +1,261 @@
+use nalgebra_glm::Vec3;
+use std::collections::{HashMap, HashSet};
+use crate::cube::Cube;
+use crate::material::Material;
+use crate::ray_intersect::{Intersect, SceneIntersect};
+
+// Celda entera dentro de la cuadrícula de bloques
+type Cell = (i32, i32, i32);
+
+// Bloques del mismo tamaño y material que se pueden fusionar entre sí, con el índice del cubo original de cada celda
+struct BlockGroup {
+    size: f32,
+    material: Material,
+    cells: HashMap<Cell, usize>,
+}
+
+// Cubos originales que cubre una caja: una rejilla de `dims` celdas de `size` unidades
+struct BoxSource {
+    size: f32,
+    dims: Cell,
+    indices: Vec<usize>,  // Índice en la escena de cada celda, con X variando más rápido, luego Z y por último Y
+}
+
+/// Escena con los bloques fusionados. Los rayos se prueban contra las cajas, pero cada intersección
+/// se traduce al cubo original que se alcanzó, para que el índice y la selección con el mouse
+/// se refieran a un bloque de la escena y no a la caja entera.
+pub struct MergedBlocks {
+    pub boxes: Vec<Cube>,
+    pub cubes: Vec<Cube>,  // Cubos originales, en el orden de la escena
+    sources: Vec<BoxSource>,
+}
+
+impl MergedBlocks {
+    // Índice del cubo original de la caja `index` que contiene el punto `point` de su cara con normal `normal`
+    fn source(&self, index: usize, point: &Vec3, normal: &Vec3) -> usize {
+        let source = &self.sources[index];
+        // Nos metemos media celda dentro de la caja para no caer en la celda vecina
+        let local = (point - normal * (source.size * 0.5) - self.boxes[index].min) / source.size;
+        let cell = |v: f32, n: i32| (v.floor() as i32).clamp(0, n - 1);
+        let (dx, dy, dz) = source.dims;
+        let (x, y, z) = (cell(local.x, dx), cell(local.y, dy), cell(local.z, dz));
+        source.indices[(x + z * dx + y * dx * dz) as usize]
+    }
+}
+
+impl SceneIntersect for MergedBlocks {
+    fn closest_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3, max_distance: f32) -> Intersect {
+        let intersect = self.boxes.closest_intersect(ray_origin, ray_direction, max_distance);
+        if !intersect.is_intersecting {
+            return intersect;
+        }
+        Intersect { object: self.source(intersect.object, &intersect.point, &intersect.normal), ..intersect }
+    }
+
+    fn occluded(&self, ray_origin: &Vec3, ray_direction: &Vec3, max_distance: f32) -> bool {
+        self.boxes.occluded(ray_origin, ray_direction, max_distance)
+    }
+
+    fn pick(&self, ray_origin: &Vec3, ray_direction: &Vec3, max_distance: f32) -> Option<Cube> {
+        let intersect = self.closest_intersect(ray_origin, ray_direction, max_distance);
+        intersect.is_intersecting.then(|| self.cubes[intersect.object].clone())
+    }
+}
+
+/// Fusiona bloques adyacentes del mismo material en cajas más grandes.
+/// Solo se fusionan cubos bien formados y alineados a su propia cuadrícula; el resto
+/// (cajas invertidas, tamaños irregulares) se copia sin cambios. Las cajas fusionadas
+/// guardan `uv_tile` para que la textura se siga repitiendo una vez por bloque.
+pub fn merge_blocks(objects: &[Cube]) -> MergedBlocks {
+    let mut groups: Vec<BlockGroup> = Vec::new();
+    let mut merged = MergedBlocks { boxes: Vec::new(), cubes: objects.to_vec(), sources: Vec::new() };
+
+    for (index, object) in objects.iter().enumerate() {
+        let Some((size, cell)) = grid_cell(object) else {
+            merged.boxes.push(object.clone());
+            merged.sources.push(BoxSource { size: 1.0, dims: (1, 1, 1), indices: vec![index] });
+            continue;
+        };
+
+        // Buscamos un grupo con el mismo tamaño y material; de los duplicados se queda el primero
+        match groups.iter_mut().find(|g| g.size == size && g.material == object.material) {
+            Some(group) => {
+                group.cells.entry(cell).or_insert(index);
+            }
+            None => {
+                let cells = HashMap::from([(cell, index)]);
+                groups.push(BlockGroup { size, material: object.material.clone(), cells });
+            }
+        }
+    }
+
+    for group in &groups {
+        merge_group(group, &mut merged);
+    }
+
+    merged
+}
+
+// Devuelve el tamaño y la celda del cubo si es un bloque cúbico alineado a la cuadrícula
+fn grid_cell(cube: &Cube) -> Option<(f32, Cell)> {
+    let extent = cube.max - cube.min;
+    let size = extent.x;
+
+    if size <= 0.0 || extent.y != size || extent.z != size || cube.uv_tile.is_some_and(|t| t != size) {
+        return None;
+    }
+
+    let cell = cube.min / size;
+    let rounded = Vec3::new(cell.x.round(), cell.y.round(), cell.z.round());
+    if rounded * size != cube.min {
+        return None;
+    }
+
+    Some((size, (rounded.x as i32, rounded.y as i32, rounded.z as i32)))
+}
+
+// Fusión voraz: crece cada caja en X, luego en Z y por último en Y
+fn merge_group(group: &BlockGroup, merged: &mut MergedBlocks) {
+    let mut cells: Vec<Cell> = group.cells.keys().copied().collect();
+    cells.sort_by_key(|&(x, y, z)| (y, z, x));
+
+    let mut visited: HashSet<Cell> = HashSet::new();
+    let free = |cell: Cell, visited: &HashSet<Cell>| group.cells.contains_key(&cell) && !visited.contains(&cell);
+
+    for &(x0, y0, z0) in &cells {
+        if visited.contains(&(x0, y0, z0)) {
+            continue;
+        }
+
+        let mut dx = 1;
+        while free((x0 + dx, y0, z0), &visited) {
+            dx += 1;
+        }
+
+        let mut dz = 1;
+        while (0..dx).all(|i| free((x0 + i, y0, z0 + dz), &visited)) {
+            dz += 1;
+        }
+
+        let mut dy = 1;
+        while (0..dx).all(|i| (0..dz).all(|k| free((x0 + i, y0 + dy, z0 + k), &visited))) {
+            dy += 1;
+        }
+
+        let mut indices = Vec::new();
+        for j in 0..dy {
+            for k in 0..dz {
+                for i in 0..dx {
+                    visited.insert((x0 + i, y0 + j, z0 + k));
+                    indices.push(group.cells[&(x0 + i, y0 + j, z0 + k)]);
+                }
+            }
+        }
+
+        let size = group.size;
+        merged.sources.push(BoxSource { size, dims: (dx, dy, dz), indices });
+        merged.boxes.push(Cube {
+            min: Vec3::new(x0 as f32, y0 as f32, z0 as f32) * size,
+            max: Vec3::new((x0 + dx) as f32, (y0 + dy) as f32, (z0 + dz) as f32) * size,
+            material: group.material.clone(),
+            uv_tile: Some(size),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::ray_intersect::SceneIntersect;
+
+    fn block(x: i32, y: i32, z: i32, material: &Material) -> Cube {
+        let min = Vec3::new(x as f32, y as f32, z as f32) * 0.5;
+        Cube { min, max: min + Vec3::new(0.5, 0.5, 0.5), material: material.clone(), uv_tile: None }
+    }
+
+    fn red() -> Material {
+        Material::new("rojo", Some(Color::new(255, 0, 0)), None, 0, 0, 0.0, [1.0, 0.0], None, 0.0)
+    }
+
+    // Losa de 3 x 4 bloques grises con una columna roja encima y un cubo fuera de la cuadrícula
+    fn scene() -> Vec<Cube> {
+        let mut objects = Vec::new();
+        for x in 0..3 {
+            for z in 0..4 {
+                objects.push(block(x, 0, z, &Material::default()));
+            }
+        }
+        objects.push(block(1, 1, 1, &red()));
+        objects.push(block(1, 2, 1, &red()));
+        objects.push(Cube {
+            min: Vec3::new(2.2, 0.5, 0.3),
+            max: Vec3::new(2.6, 0.8, 0.6),
+            material: Material::default(),
+            uv_tile: None,
+        });
+        objects
+    }
+
+    #[test]
+    fn slab_becomes_one_box() {
+        let objects: Vec<Cube> = (0..3).flat_map(|x| (0..4).map(move |z| block(x, 0, z, &Material::default()))).collect();
+        let merged = merge_blocks(&objects).boxes;
+
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].min, Vec3::new(0.0, 0.0, 0.0));
+        assert_eq!(merged[0].max, Vec3::new(1.5, 0.5, 2.0));
+        assert_eq!(merged[0].uv_tile, Some(0.5));
+        assert_eq!(merged[0].material, Material::default());
+    }
+
+    #[test]
+    fn materials_and_irregular_cubes_stay_apart() {
+        let merged = merge_blocks(&scene()).boxes;
+
+        // La losa, la columna roja y el cubo irregular tal como estaba
+        assert_eq!(merged.len(), 3);
+        let column = merged.iter().find(|cube| cube.material == red()).unwrap();
+        assert_eq!((column.min, column.max), (Vec3::new(0.5, 0.5, 0.5), Vec3::new(1.0, 1.5, 1.0)));
+        assert!(merged.iter().any(|cube| cube.uv_tile.is_none() && cube.min == Vec3::new(2.2, 0.5, 0.3)));
+    }
+
+    #[test]
+    fn pick_returns_the_original_block() {
+        let objects = scene();
+        let merged = merge_blocks(&objects);
+
+        // Desde arriba hacia el bloque (2, 0, 3) de la losa, y de frente hacia la base de la columna roja
+        let picked = merged.pick(&Vec3::new(1.2, 5.0, 1.7), &Vec3::new(0.0, -1.0, 0.0), 20.0);
+        assert_eq!(picked, Some(objects[2 * 4 + 3].clone()));
+        let picked = merged.pick(&Vec3::new(0.7, 0.6, -3.0), &Vec3::new(0.0, 0.0, 1.0), 20.0);
+        assert_eq!(picked, Some(objects[12].clone()));
+    }
+
+    #[test]
+    fn merged_and_unmerged_hits_match() {
+        let objects = scene();
+        let merged = merge_blocks(&objects);
+        let origin = Vec3::new(0.37, 3.1, -2.3);
+
+        // Rayos hacia puntos que no caen justo sobre las juntas entre bloques
+        for i in 0..40 {
+            for j in 0..40 {
+                let target = Vec3::new(-0.5 + i as f32 * 0.0713, 0.2, -0.5 + j as f32 * 0.0791);
+                let direction = (target - origin).normalize();
+                let a = objects.closest_intersect(&origin, &direction, 20.0);
+                let b = merged.closest_intersect(&origin, &direction, 20.0);
+
+                assert_eq!(a.is_intersecting, b.is_intersecting);
+                if a.is_intersecting {
+                    assert!((a.distance - b.distance).abs() < 1e-5, "{} != {}", a.distance, b.distance);
+                    assert_eq!(a.normal, b.normal);
+                    assert_eq!(a.material, b.material);
+                    // El índice es el del cubo original, no el de la caja fusionada
+                    assert_eq!(a.object, b.object);
+                    assert!((a.uv[0] - b.uv[0]).abs() < 1e-4 && (a.uv[1] - b.uv[1]).abs() < 1e-4, "{:?} != {:?}", a.uv, b.uv);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use nalgebra_glm::Vec3;
+use std::fmt::Write as _;
+use std::fs;
+use crate::camera::Camera;
+
+// Posición de la cámara en un instante del recorrido
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Keyframe {
+    pub time: f32,    // Segundos desde el inicio del recorrido
+    pub eye: Vec3,
+    pub center: Vec3,
+    pub fov: f32,     // En radianes
+}
+
+// Cómo se unen los keyframes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interpolation {
+    CatmullRom,  // Pasa por todos los keyframes
+    Bezier,      // Usa los keyframes como puntos de control de una sola curva (más suave, no pasa por los del medio)
+}
+
+// Curva de velocidad aplicada al tiempo de todo el recorrido
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    fn apply(&self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// Recorrido de cámara: keyframes ordenados por tiempo más la forma de interpolarlos.
+/// Se guarda como texto, una línea por keyframe:
+///
+/// ```text
+/// interpolation catmull-rom
+/// easing ease-in-out
+/// key 0.0  0 10 -10  0 5 0  45
+/// key 4.0  10 10 0   0 5 0  45
+/// ```
+///
+/// Cada `key` es: tiempo, ojo (x y z), centro (x y z) y campo de visión en grados.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CameraPath {
+    pub keyframes: Vec<Keyframe>,
+    pub interpolation: Interpolation,
+    pub easing: Easing,
+}
+
+impl Default for CameraPath {
+    fn default() -> Self {
+        CameraPath { keyframes: Vec::new(), interpolation: Interpolation::CatmullRom, easing: Easing::EaseInOut }
+    }
+}
+
+impl CameraPath {
+    // Agrega la vista actual de la cámara al final del recorrido
+    pub fn push(&mut self, camera: &Camera, time: f32) {
+        self.keyframes.push(Keyframe { time, eye: camera.eye, center: camera.center, fov: camera.fov });
+        self.keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+    }
+
+    pub fn duration(&self) -> f32 {
+        match (self.keyframes.first(), self.keyframes.last()) {
+            (Some(first), Some(last)) => last.time - first.time,
+            _ => 0.0,
+        }
+    }
+
+    // Ojo, centro y campo de visión a los `time` segundos del inicio
+    pub fn sample(&self, time: f32) -> Option<(Vec3, Vec3, f32)> {
+        let first = self.keyframes.first()?;
+        let duration = self.duration();
+        if self.keyframes.len() == 1 || duration <= 0.0 {
+            return Some((first.eye, first.center, first.fov));
+        }
+
+        let progress = self.easing.apply((time / duration).clamp(0.0, 1.0));
+
+        let key = match self.interpolation {
+            Interpolation::CatmullRom => {
+                // Buscamos el tramo que contiene el tiempo ya suavizado
+                let time = first.time + progress * duration;
+                let i = self.keyframes.windows(2).position(|pair| time <= pair[1].time).unwrap_or(self.keyframes.len() - 2);
+                let k = |j: isize| self.keyframes[(i as isize + j).clamp(0, self.keyframes.len() as isize - 1) as usize];
+                let (k0, k1, k2, k3) = (k(-1), k(0), k(1), k(2));
+
+                let span = k2.time - k1.time;
+                let t = if span > 0.0 { (time - k1.time) / span } else { 0.0 };
+                Keyframe {
+                    time,
+                    eye: catmull_rom(k0.eye, k1.eye, k2.eye, k3.eye, t),
+                    center: catmull_rom(k0.center, k1.center, k2.center, k3.center, t),
+                    fov: k1.fov + (k2.fov - k1.fov) * t,
+                }
+            }
+            Interpolation::Bezier => Keyframe {
+                time: first.time + progress * duration,
+                eye: bezier(self.keyframes.iter().map(|k| k.eye).collect(), progress),
+                center: bezier(self.keyframes.iter().map(|k| k.center).collect(), progress),
+                fov: bezier(self.keyframes.iter().map(|k| Vec3::new(k.fov, 0.0, 0.0)).collect(), progress).x,
+            },
+        };
+
+        Some((key.eye, key.center, key.fov))
+    }
+
+    // Coloca la cámara donde indica el recorrido; devuelve false si el recorrido está vacío
+    pub fn apply(&self, camera: &mut Camera, time: f32) -> bool {
+        match self.sample(time) {
+            Some((eye, center, fov)) => {
+                camera.eye = eye;
+                camera.center = center;
+                camera.fov = fov;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn load(path: &str) -> Result<CameraPath, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("No se pudo leer {}: {}", path, e))?;
+        let mut camera_path = CameraPath::default();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let mut words = line.split_whitespace();
+            let error = |message: &str| format!("{}:{}: {}", path, number + 1, message);
+
+            match words.next() {
+                None => {}
+                Some("interpolation") => camera_path.interpolation = match words.next() {
+                    Some("catmull-rom") => Interpolation::CatmullRom,
+                    Some("bezier") => Interpolation::Bezier,
+                    _ => return Err(error("interpolación desconocida (usa catmull-rom o bezier)")),
+                },
+                Some("easing") => camera_path.easing = match words.next() {
+                    Some("linear") => Easing::Linear,
+                    Some("ease-in") => Easing::EaseIn,
+                    Some("ease-out") => Easing::EaseOut,
+                    Some("ease-in-out") => Easing::EaseInOut,
+                    _ => return Err(error("suavizado desconocido (usa linear, ease-in, ease-out o ease-in-out)")),
+                },
+                Some("key") => {
+                    let values: Vec<f32> = words.map(str::parse).collect::<Result<_, _>>()
+                        .map_err(|_| error("los valores de `key` deben ser números"))?;
+                    if values.len() != 8 {
+                        return Err(error("`key` necesita tiempo, ojo (x y z), centro (x y z) y campo de visión"));
+                    }
+                    camera_path.keyframes.push(Keyframe {
+                        time: values[0],
+                        eye: Vec3::new(values[1], values[2], values[3]),
+                        center: Vec3::new(values[4], values[5], values[6]),
+                        fov: values[7].to_radians(),
+                    });
+                }
+                Some(other) => return Err(error(&format!("instrucción desconocida: {}", other))),
+            }
+        }
+
+        camera_path.keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Ok(camera_path)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let mut text = String::new();
+        let interpolation = match self.interpolation {
+            Interpolation::CatmullRom => "catmull-rom",
+            Interpolation::Bezier => "bezier",
+        };
+        let easing = match self.easing {
+            Easing::Linear => "linear",
+            Easing::EaseIn => "ease-in",
+            Easing::EaseOut => "ease-out",
+            Easing::EaseInOut => "ease-in-out",
+        };
+        let _ = writeln!(text, "interpolation {}", interpolation);
+        let _ = writeln!(text, "easing {}", easing);
+        for k in &self.keyframes {
+            let _ = writeln!(
+                text, "key {} {} {} {} {} {} {} {}",
+                k.time, k.eye.x, k.eye.y, k.eye.z, k.center.x, k.center.y, k.center.z, k.fov.to_degrees()
+            );
+        }
+
+        fs::write(path, text).map_err(|e| format!("No se pudo guardar {}: {}", path, e))
+    }
+}
+
+// Spline de Catmull-Rom uniforme entre p1 y p2
+fn catmull_rom(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, t: f32) -> Vec3 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (p1 * 2.0 + (p2 - p0) * t + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2 + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3) * 0.5
+}
+
+// Curva de Bézier por el algoritmo de De Casteljau
+fn bezier(mut points: Vec<Vec3>, t: f32) -> Vec3 {
+    while points.len() > 1 {
+        points = points.windows(2).map(|pair| pair[0] + (pair[1] - pair[0]) * t).collect();
+    }
+    points[0]
+}
@@ -1,4 +1,5 @@
 use nalgebra_glm::Vec3;
+use crate::cube::Cube;
 use crate::material::Material;
 
 #[derive(Debug, Clone)]  // Eliminamos Copy, solo dejamos Clone
@@ -7,18 +8,24 @@ pub struct Intersect {
     pub point: Vec3,
     pub normal: Vec3,
     pub distance: f32,
+    pub uv: [f32; 2],
     pub is_intersecting: bool,
     pub material: Material,
+    pub object: usize,  // Índice del cubo alcanzado dentro de la escena (ver `SceneIntersect`)
+    pub tests: u32,     // Cubos o celdas que se probaron para encontrarlo (para el mapa de calor de depuración)
 }
 
 impl Intersect {
-    pub fn new(point: Vec3, normal: Vec3, distance: f32, material: Material) -> Self {
+    pub fn new(point: Vec3, normal: Vec3, distance: f32, uv: [f32; 2], material: Material) -> Self {
         Intersect {
             point,
             normal,
             distance,
+            uv,
             is_intersecting: true,
             material,
+            object: 0,
+            tests: 0,
         }
     }
 
@@ -27,12 +34,71 @@ impl Intersect {
             point: Vec3::zeros(),
             normal: Vec3::zeros(),
             distance: 0.0,
+            uv: [0.0, 0.0],
             is_intersecting: false,
             material: Material::default(),  // Asegúrate de que Material::black() siga siendo válido.
+            object: 0,
+            tests: 0,
         }
     }
 }
 
+// Distancia a la que un punto se considera sobre un plano al buscar aristas
+pub const EDGE_EPSILON: f32 = 1e-4;
+
+/// Sobre una arista el rayo cruza dos o tres planos a la vez y, por redondeo, cada estructura
+/// puede devolver cualquiera de esas caras, incluso una que comparte con un vecino y no se ve.
+/// Para que cubos y chunks den lo mismo, se miran las celdas junto a la arista a las que se llega
+/// cruzando un solo plano (`solid` indica si un punto cae dentro de un bloque): gana la cara que
+/// da al aire y, entre varias, la que el rayo mira más de frente. `axes` son los ejes de los
+/// planos que pasan por el punto. Devuelve la normal y un punto dentro del bloque dueño de la cara.
+pub fn edge_normal(point: &Vec3, ray_direction: &Vec3, axes: &[usize], solid: impl Fn(&Vec3) -> bool) -> (Vec3, Vec3) {
+    let delta = 1e-3;
+    let mut step = Vec3::zeros();
+    for &axis in axes {
+        step[axis] = ray_direction[axis].signum();
+    }
+
+    // El rayo llega desde la celda `point - step`; cruzando el plano de un eje se pasa a su vecina
+    let across = |axis: usize| {
+        let mut probe = point - step * delta;
+        probe[axis] += 2.0 * step[axis] * delta;
+        probe
+    };
+    let visible: Vec<usize> = axes.iter().copied().filter(|&axis| solid(&across(axis))).collect();
+    let candidates = if visible.is_empty() { axes } else { &visible[..] };
+    let axis = candidates.iter().copied()
+        .max_by(|&a, &b| ray_direction[a].abs().total_cmp(&ray_direction[b].abs()).then(b.cmp(&a)))
+        .unwrap_or(0);
+
+    let mut normal = Vec3::zeros();
+    normal[axis] = -step[axis];
+    let owner = if visible.contains(&axis) { across(axis) } else { point + step * delta };
+    (normal, owner)
+}
+
+/// Distancia hasta el plano de la cara de la caja `min`..`max` que mira hacia `normal`, con la
+/// misma cuenta que `Cube::ray_intersect` para que cubos y chunks den el mismo punto
+pub fn face_distance(ray_origin: &Vec3, ray_direction: &Vec3, normal: &Vec3, min: &Vec3, max: &Vec3) -> f32 {
+    let axis = (0..3).find(|&axis| normal[axis] != 0.0).unwrap_or(0);
+    let plane = if normal[axis] < 0.0 { min[axis] } else { max[axis] };
+    (plane - ray_origin[axis]) * (1.0 / ray_direction[axis])
+}
+
 pub trait RayIntersect {
     fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect;
 }
+
+// Geometría completa de una escena que el renderizador puede recorrer
+pub trait SceneIntersect {
+    // Intersección más cercana del rayo, ignorando lo que esté más allá de `max_distance`.
+    // `Intersect::object` es el índice del cubo en la escena (también dentro de una caja fusionada),
+    // o un identificador de la celda en mundos por chunks
+    fn closest_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3, max_distance: f32) -> Intersect;
+
+    // Indica si algo corta el rayo antes de `max_distance` (para los rayos de sombra)
+    fn occluded(&self, ray_origin: &Vec3, ray_direction: &Vec3, max_distance: f32) -> bool;
+
+    // Bloque de la escena que alcanza primero el rayo (para seleccionar bloques con el mouse)
+    fn pick(&self, ray_origin: &Vec3, ray_direction: &Vec3, max_distance: f32) -> Option<Cube>;
+}
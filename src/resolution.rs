@@ -0,0 +1,61 @@
+/// Escala de resolución dinámica del visor: mientras la cámara se mueve la escala del framebuffer
+/// se ajusta para acercarse al tiempo por cuadro objetivo, y con la cámara quieta sube hasta el máximo
+/// mientras cada cuadro no tarde más que unas pocas veces el objetivo.
+pub struct DynamicResolution {
+    pub target_frame_time: f32,  // Segundos
+    pub min_scale: f32,
+    pub max_scale: f32,
+    scale: f32,
+    moving: bool,  // Si la cámara se movía en el cuadro anterior
+}
+
+// Con la cámara quieta un cuadro puede tardar hasta este múltiplo del tiempo objetivo: la imagen se ve
+// con más detalle y el visor sigue respondiendo a las teclas
+const IDLE_BUDGET: f32 = 4.0;
+
+impl DynamicResolution {
+    pub fn new(target_fps: f32, min_scale: f32, max_scale: f32, initial_scale: f32) -> Result<Self, String> {
+        if target_fps <= 0.0 {
+            return Err("--target-fps debe ser mayor que 0".to_string());
+        }
+        if min_scale <= 0.0 || min_scale > max_scale || max_scale > 1.0 {
+            return Err(format!("Escalas inválidas: mínima {} y máxima {} (usa 0 < mínima <= máxima <= 1)", min_scale, max_scale));
+        }
+        Ok(DynamicResolution {
+            target_frame_time: 1.0 / target_fps,
+            min_scale,
+            max_scale,
+            scale: initial_scale.clamp(min_scale, max_scale),
+            moving: false,
+        })
+    }
+
+    // Ajusta la escala después de un cuadro que tardó `frame_time` segundos en renderizarse.
+    // El costo crece con el número de píxeles, o sea con el cuadrado de la escala, así que la escala
+    // que llega a un tiempo dado es la actual por la raíz de la proporción entre los tiempos
+    pub fn update(&mut self, frame_time: f32, moving: bool) {
+        let frame_time = frame_time.max(1e-4);
+        let factor = if moving && !self.moving {
+            // Al empezar a moverse se salta directo a la escala estimada: los cuadros refinados
+            // pueden tardar mucho y bajar de a poco dejaría la cámara trabada varios cuadros
+            (self.target_frame_time / frame_time).sqrt()
+        } else if moving {
+            // El cambio por cuadro se limita para que la imagen no parpadee
+            (self.target_frame_time / frame_time).sqrt().clamp(0.85, 1.1)
+        } else {
+            // Quieta se refina de a poco, pero sin pasar del tiempo permitido (con antialiasing o una
+            // pasada de depuración cara la escala incluso baja)
+            (IDLE_BUDGET * self.target_frame_time / frame_time).sqrt().min(1.1)
+        };
+        self.scale = (self.scale * factor).clamp(self.min_scale, self.max_scale);
+        self.moving = moving;
+    }
+
+    // Tamaño del framebuffer para una ventana de `width` x `height`. La escala se redondea a pasos
+    // de 5% para no cambiar de tamaño en cada cuadro por diferencias mínimas
+    pub fn framebuffer_size(&self, width: usize, height: usize) -> (usize, usize) {
+        let scale = (self.scale * 20.0).round() / 20.0;
+        let scale = scale.clamp(self.min_scale, self.max_scale);
+        (((width as f32 * scale) as usize).max(1), ((height as f32 * scale) as usize).max(1))
+    }
+}
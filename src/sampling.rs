@@ -0,0 +1,339 @@
+use nalgebra_glm::Vec3;
+use rayon::prelude::*;
+use std::f32::consts::PI;
+use crate::camera::{lens_sample, Camera};
+use crate::constants;
+
+// Cómo se reparten los rayos dentro de cada píxel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pattern {
+    Grid,      // En el centro de cada celda de una cuadrícula N x N
+    Jittered,  // En un punto al azar dentro de cada celda (estratificado)
+}
+
+// Filtro de reconstrucción: cuánto pesa cada rayo según su distancia al centro del píxel.
+// Los más anchos toman también rayos de los píxeles vecinos
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Filter {
+    Box,       // Promedio simple de los rayos del píxel
+    Tent,      // Triangular, radio de 1 píxel
+    Gaussian,  // Gaussiana (sigma 0.5), radio de 1.5 píxeles
+    Mitchell,  // Mitchell-Netravali (B = C = 1/3), radio de 2 píxeles; realza un poco los bordes
+}
+
+impl Filter {
+    pub fn parse(name: &str) -> Result<Filter, String> {
+        match name {
+            "box" => Ok(Filter::Box),
+            "tent" => Ok(Filter::Tent),
+            "gaussian" => Ok(Filter::Gaussian),
+            "mitchell" => Ok(Filter::Mitchell),
+            _ => Err(format!("Filtro desconocido: {} (usa box, tent, gaussian o mitchell)", name)),
+        }
+    }
+
+    fn radius(&self) -> f32 {
+        match self {
+            Filter::Box => 0.5,
+            Filter::Tent => 1.0,
+            Filter::Gaussian => 1.5,
+            Filter::Mitchell => 2.0,
+        }
+    }
+
+    // Peso en una dimensión para un rayo a `d` píxeles del centro
+    fn weight_1d(&self, d: f32) -> f32 {
+        let x = d.abs();
+        match self {
+            Filter::Box => if x <= 0.5 { 1.0 } else { 0.0 },
+            Filter::Tent => (1.0 - x).max(0.0),
+            Filter::Gaussian => {
+                let gaussian = |x: f32| (-x * x / (2.0 * 0.5 * 0.5)).exp();
+                (gaussian(x) - gaussian(self.radius())).max(0.0)
+            }
+            Filter::Mitchell => {
+                let (b, c) = (1.0 / 3.0, 1.0 / 3.0);
+                if x < 1.0 {
+                    ((12.0 - 9.0 * b - 6.0 * c) * x.powi(3) + (-18.0 + 12.0 * b + 6.0 * c) * x * x + (6.0 - 2.0 * b)) / 6.0
+                } else if x < 2.0 {
+                    ((-b - 6.0 * c) * x.powi(3) + (6.0 * b + 30.0 * c) * x * x + (-12.0 * b - 48.0 * c) * x + (8.0 * b + 24.0 * c)) / 6.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+
+    fn weight(&self, dx: f32, dy: f32) -> f32 {
+        self.weight_1d(dx) * self.weight_1d(dy)
+    }
+}
+
+/// Configuración del antialiasing: `grid` x `grid` rayos por píxel con el patrón y filtro elegidos.
+/// Con `adaptive` se lanza primero un rayo por píxel y los demás solo donde un vecino difiere
+/// más que ese umbral (0..1 en cualquier canal).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sampling {
+    pub grid: usize,
+    pub pattern: Pattern,
+    pub filter: Filter,
+    pub adaptive: Option<f32>,
+}
+
+impl Sampling {
+    // `samples` rayos por píxel en cuadrícula regular (se redondea al cuadrado perfecto siguiente), promediados
+    pub fn new(samples: usize) -> Self {
+        Sampling {
+            grid: (samples.max(1) as f32).sqrt().ceil() as usize,
+            pattern: Pattern::Grid,
+            filter: Filter::Box,
+            adaptive: None,
+        }
+    }
+
+    // Desplazamientos dentro del píxel (x, y), en [-0.5, 0.5]
+    fn offsets(&self, x: usize, y: usize, offsets: &mut Vec<(f32, f32)>) {
+        let grid = self.grid as f32;
+        for i in 0..self.grid {
+            for j in 0..self.grid {
+                let (u, v) = match self.pattern {
+                    Pattern::Grid => (0.5, 0.5),
+                    Pattern::Jittered => {
+                        let index = (i * self.grid + j) as u32;
+                        (random(x as u32, y as u32, 2 * index), random(x as u32, y as u32, 2 * index + 1))
+                    }
+                };
+                offsets.push(((j as f32 + u) / grid - 0.5, (i as f32 + v) / grid - 0.5));
+            }
+        }
+    }
+}
+
+// Rayo ya trazado: desplazamiento dentro de su píxel y valor
+type Sample = (f32, f32, Vec3);
+
+/// Renderiza una imagen de `width` x `height` con el antialiasing de `sampling`. `trace` devuelve el valor
+/// de un rayo (color de 8 bits o radiancia lineal); `scale` lleva esos valores a 0..1 para el umbral adaptativo.
+pub fn render_samples<F>(camera: &Camera, width: usize, height: usize, sampling: &Sampling, scale: f32, trace: F) -> Vec<Vec3>
+where
+    F: Fn(&Vec3, &Vec3) -> Vec3 + Sync,
+{
+    let (w, h) = (width as f32, height as f32);
+    let trace_offsets = |x: usize, y: usize, offsets: &[(f32, f32)], samples: &mut Vec<Sample>| {
+        for &(dx, dy) in offsets {
+            let value = trace_point(camera, x as f32 + dx, y as f32 + dy, w, h, &trace);
+            samples.push((dx, dy, value));
+        }
+    };
+
+    // Con caja y sin adaptativo cada píxel solo usa sus propios rayos: no hace falta guardarlos
+    if sampling.filter == Filter::Box && sampling.adaptive.is_none() {
+        let mut image = vec![Vec3::zeros(); width * height];
+        image.par_chunks_mut(width.max(1)).enumerate().for_each(|(y, row)| {
+            let mut offsets = Vec::new();
+            let mut samples = Vec::new();
+            for (x, pixel) in row.iter_mut().enumerate() {
+                offsets.clear();
+                samples.clear();
+                sampling.offsets(x, y, &mut offsets);
+                trace_offsets(x, y, &offsets, &mut samples);
+                *pixel = samples.iter().fold(Vec3::zeros(), |sum, s| sum + s.2) / samples.len() as f32;
+            }
+        });
+        return image;
+    }
+
+    // Primera pasada: todos los rayos, o solo uno en el centro si es adaptativo
+    let mut pixels: Vec<Vec<Sample>> = vec![Vec::new(); width * height];
+    pixels.par_chunks_mut(width.max(1)).enumerate().for_each(|(y, row)| {
+        let mut offsets = Vec::new();
+        for (x, samples) in row.iter_mut().enumerate() {
+            offsets.clear();
+            match sampling.adaptive {
+                Some(_) => offsets.push((0.0, 0.0)),
+                None => sampling.offsets(x, y, &mut offsets),
+            }
+            trace_offsets(x, y, &offsets, samples);
+        }
+    });
+
+    // Adaptativo: se refinan los píxeles que difieren de algún vecino más que el umbral
+    if let Some(threshold) = sampling.adaptive {
+        let first: Vec<Vec3> = pixels.iter().map(|samples| samples[0].2).collect();
+        let contrast = |a: Vec3, b: Vec3| {
+            (0..3).map(|c| ((a[c] * scale).clamp(0.0, 1.0) - (b[c] * scale).clamp(0.0, 1.0)).abs()).fold(0.0, f32::max)
+        };
+
+        pixels.par_chunks_mut(width.max(1)).enumerate().for_each(|(y, row)| {
+            let mut offsets = Vec::new();
+            for (x, samples) in row.iter_mut().enumerate() {
+                let center = first[y * width + x];
+                let neighbours = [
+                    (x > 0).then(|| first[y * width + x - 1]),
+                    (x + 1 < width).then(|| first[y * width + x + 1]),
+                    (y > 0).then(|| first[(y - 1) * width + x]),
+                    (y + 1 < height).then(|| first[(y + 1) * width + x]),
+                ];
+                if neighbours.iter().flatten().any(|&n| contrast(center, n) > threshold) {
+                    // La muestra del centro se descarta: los N x N rayos ya cubren el píxel
+                    // y de lo contrario el centro contaría dos veces en el filtro
+                    samples.clear();
+                    offsets.clear();
+                    sampling.offsets(x, y, &mut offsets);
+                    trace_offsets(x, y, &offsets, samples);
+                }
+            }
+        });
+    }
+
+    // Reconstrucción: cada píxel junta los rayos de los vecinos que caen dentro del radio del filtro
+    let reach = (sampling.filter.radius() - 0.5).ceil() as isize;
+    let mut image = vec![Vec3::zeros(); width * height];
+    image.par_chunks_mut(width.max(1)).enumerate().for_each(|(y, row)| {
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let mut sum = Vec3::zeros();
+            let mut total = 0.0;
+            for ny in (y as isize - reach).max(0)..=(y as isize + reach).min(height as isize - 1) {
+                for nx in (x as isize - reach).max(0)..=(x as isize + reach).min(width as isize - 1) {
+                    for &(dx, dy, value) in &pixels[ny as usize * width + nx as usize] {
+                        let weight = sampling.filter.weight(nx as f32 + dx - x as f32, ny as f32 + dy - y as f32);
+                        sum += value * weight;
+                        total += weight;
+                    }
+                }
+            }
+
+            // Si los pesos se anulan (lóbulos negativos de Mitchell) queda el promedio de los rayos propios
+            *pixel = if total > 1e-6 {
+                sum / total
+            } else {
+                let own = &pixels[y * width + x];
+                own.iter().fold(Vec3::zeros(), |sum, s| sum + s.2) / own.len() as f32
+            };
+        }
+    });
+    image
+}
+
+// Valor del punto (x, y) de la imagen; si la lente tiene apertura, promedia varios rayos repartidos
+// sobre la lente. El giro por punto cambia el patrón de un píxel a otro para que el desenfoque no forme copias
+fn trace_point<F: Fn(&Vec3, &Vec3) -> Vec3>(camera: &Camera, x: f32, y: f32, width: f32, height: f32, trace: &F) -> Vec3 {
+    let (ray_origin, ray_direction) = camera.primary_ray(x, y, width, height);
+    if camera.aperture <= 0.0 {
+        return trace(&ray_origin, &ray_direction);
+    }
+
+    let rotation = 2.0 * PI * (52.982_92 * (0.067_110_56 * x + 0.005_837_15 * y).fract()).fract();
+    let mut sum = Vec3::zeros();
+    for k in 0..constants::DOF_SAMPLES {
+        let (origin, direction) = camera.lens_ray(ray_origin, ray_direction, lens_sample(k, constants::DOF_SAMPLES, rotation));
+        sum += trace(&origin, &direction);
+    }
+    sum / constants::DOF_SAMPLES as f32
+}
+
+// Número pseudoaleatorio en [0, 1) que solo depende del píxel y del índice, para que
+// el mismo render salga siempre igual
+fn random(x: u32, y: u32, index: u32) -> f32 {
+    let mut hash = x.wrapping_mul(0x8da6_b343) ^ y.wrapping_mul(0xd816_3841) ^ index.wrapping_mul(0xcb1a_b31f);
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x7feb_352d);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x846c_a68b);
+    hash ^= hash >> 16;
+    (hash >> 8) as f32 / (1u32 << 24) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::Projection;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const FILTERS: [Filter; 4] = [Filter::Box, Filter::Tent, Filter::Gaussian, Filter::Mitchell];
+
+    // Cámara ortográfica en la que el origen de cada rayo, corrido `width / 2`, es la coordenada x del píxel
+    fn image_camera(height: usize) -> Camera {
+        let mut camera = Camera::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 1.0, 0.0));
+        camera.projection = Projection::Orthographic { view_height: height as f32 };
+        camera
+    }
+
+    #[test]
+    fn filter_weights() {
+        for filter in FILTERS {
+            assert!(filter.weight_1d(0.0) > 0.0, "{:?}", filter);
+            assert_eq!(filter.weight_1d(0.3), filter.weight_1d(-0.3), "{:?}", filter);
+            assert_eq!(filter.weight_1d(filter.radius() + 0.01), 0.0, "{:?}", filter);
+        }
+        assert_eq!(Filter::Box.weight_1d(0.5), 1.0);
+        assert_eq!(Filter::Tent.weight_1d(0.5), 0.5);
+        assert!(Filter::Gaussian.weight_1d(0.5) < Filter::Gaussian.weight_1d(0.0));
+        assert_eq!(Filter::Gaussian.weight_1d(1.5), 0.0);
+        // Mitchell: 8/9 en el centro y 1/18 a un píxel, que suman 1 sobre la cuadrícula; negativo entre 1 y 2
+        assert!((Filter::Mitchell.weight_1d(0.0) - 8.0 / 9.0).abs() < 1e-6);
+        assert!((Filter::Mitchell.weight_1d(1.0) - 1.0 / 18.0).abs() < 1e-6);
+        assert!(Filter::Mitchell.weight_1d(1.5) < 0.0);
+        assert_eq!(Filter::Tent.weight(0.5, 0.5), 0.25);
+    }
+
+    #[test]
+    fn constant_image_stays_constant() {
+        let camera = image_camera(6);
+        let color = Vec3::new(0.25, 0.5, 1.0);
+        for filter in FILTERS {
+            for pattern in [Pattern::Grid, Pattern::Jittered] {
+                for adaptive in [None, Some(0.1)] {
+                    let sampling = Sampling { grid: 3, pattern, filter, adaptive };
+                    let image = render_samples(&camera, 8, 6, &sampling, 1.0, |_, _| color);
+                    assert!(image.iter().all(|pixel| (pixel - color).magnitude() < 1e-5), "{:?}", sampling);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn adaptive_refines_only_next_to_an_edge() {
+        let (width, height) = (8, 4);
+        let camera = image_camera(height);
+        let traced = AtomicUsize::new(0);
+        // Una columna clara de un píxel de ancho, la x = 4
+        let trace = |origin: &Vec3, _: &Vec3| {
+            traced.fetch_add(1, Ordering::Relaxed);
+            let x = origin.x + width as f32 / 2.0;
+            if (3.6..4.4).contains(&x) { Vec3::new(1.0, 1.0, 1.0) } else { Vec3::zeros() }
+        };
+
+        let sampling = Sampling { adaptive: Some(0.1), ..Sampling::new(4) };
+        let image = render_samples(&camera, width, height, &sampling, 1.0, trace);
+        // Un rayo por píxel y cuatro más en la columna y sus dos vecinas
+        assert_eq!(traced.load(Ordering::Relaxed), width * height + 3 * height * 4);
+        for y in 0..height {
+            let row: Vec<f32> = (0..width).map(|x| image[y * width + x].x).collect();
+            assert_eq!(row, [0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0]);
+        }
+
+        // Sin ningún borde no se refina nada
+        traced.store(0, Ordering::Relaxed);
+        render_samples(&camera, width, height, &sampling, 1.0, |_, _| {
+            traced.fetch_add(1, Ordering::Relaxed);
+            Vec3::zeros()
+        });
+        assert_eq!(traced.load(Ordering::Relaxed), width * height);
+    }
+
+    #[test]
+    fn adaptive_does_not_count_the_probe_twice() {
+        let (width, height) = (8, 2);
+        let camera = image_camera(height);
+        // El borde cruza el píxel 3: su rayo de prueba (x = 3) cae del lado oscuro y la mitad de sus 2 x 2 del claro
+        let trace = |origin: &Vec3, _: &Vec3| {
+            if origin.x + width as f32 / 2.0 > 3.1 { Vec3::new(1.0, 1.0, 1.0) } else { Vec3::zeros() }
+        };
+
+        let full = render_samples(&camera, width, height, &Sampling::new(4), 1.0, trace);
+        let adaptive = render_samples(&camera, width, height, &Sampling { adaptive: Some(0.1), ..Sampling::new(4) }, 1.0, trace);
+        assert_eq!(adaptive[3].x, 0.5);
+        assert_eq!(adaptive, full);
+    }
+}
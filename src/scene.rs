@@ -0,0 +1,209 @@
+use nalgebra_glm::Vec3;
+use crate::camera::Camera;
+use crate::constants::BLOCK_SIZE;
+use crate::cube::Cube;
+use crate::diorama::generate_diorama;
+use crate::optimize::{merge_blocks, MergedBlocks};
+use crate::structures::{place_structures, PlacementParams, StructureKind};
+use crate::terrain::{generate_terrain, TerrainParams};
+use crate::validate::{validate_scene, Issue};
+use crate::world::World;
+use crate::ray_intersect::{Intersect, SceneIntersect};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SceneKind {
+    Diorama,
+    Terrain(TerrainParams),
+}
+
+// Escena base más las opciones que se aplican encima (estructuras repartidas)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneSpec {
+    pub kind: SceneKind,
+    pub structures: Option<PlacementParams>,
+    pub chunked: bool,  // Guardar la escena en chunks (World) en lugar de una lista de cubos
+}
+
+// Geometría que recorre el renderizador: lista de cubos fusionados o mundo por chunks
+pub enum Geometry {
+    Cubes(MergedBlocks),
+    Chunks(World),
+}
+
+impl SceneIntersect for Geometry {
+    fn closest_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3, max_distance: f32) -> Intersect {
+        match self {
+            Geometry::Cubes(objects) => objects.closest_intersect(ray_origin, ray_direction, max_distance),
+            Geometry::Chunks(world) => world.closest_intersect(ray_origin, ray_direction, max_distance),
+        }
+    }
+
+    fn occluded(&self, ray_origin: &Vec3, ray_direction: &Vec3, max_distance: f32) -> bool {
+        match self {
+            Geometry::Cubes(objects) => objects.occluded(ray_origin, ray_direction, max_distance),
+            Geometry::Chunks(world) => world.occluded(ray_origin, ray_direction, max_distance),
+        }
+    }
+
+    fn pick(&self, ray_origin: &Vec3, ray_direction: &Vec3, max_distance: f32) -> Option<Cube> {
+        match self {
+            Geometry::Cubes(objects) => objects.pick(ray_origin, ray_direction, max_distance),
+            Geometry::Chunks(world) => world.pick(ray_origin, ray_direction, max_distance),
+        }
+    }
+}
+
+pub struct Scene {
+    pub objects: Geometry,
+    pub camera: Camera,
+    pub light_position: Vec3,
+}
+
+impl SceneSpec {
+    // Lee `--scene`, `--seed`, `--size ANCHOxPROFUNDO`, `--structures N` y `--storage cubes|chunks`
+    // de la línea de comandos
+    pub fn from_args(args: &[String]) -> Result<SceneSpec, String> {
+        let mut name = "diorama".to_string();
+        let mut params = TerrainParams::default();
+        let mut structures = 0;
+        let mut storage = None;
+
+        let mut iter = args.iter();
+        while let Some(flag) = iter.next() {
+            let mut value = || iter.next().ok_or(format!("Falta el valor de {}", flag));
+            match flag.as_str() {
+                "--scene" => name = value()?.clone(),
+                "--seed" => params.seed = value()?.parse().map_err(|_| "La semilla debe ser un número entero".to_string())?,
+                "--size" => {
+                    let size = value()?;
+                    let (w, d) = size.split_once('x').ok_or(format!("Tamaño inválido: {} (usa ANCHOxPROFUNDO)", size))?;
+                    params.width = w.parse().map_err(|_| format!("Ancho inválido: {}", w))?;
+                    params.depth = d.parse().map_err(|_| format!("Profundidad inválida: {}", d))?;
+                }
+                "--structures" => structures = value()?.parse().map_err(|_| "El número de estructuras debe ser un entero".to_string())?,
+                "--storage" => storage = Some(match value()?.as_str() {
+                    "cubes" => false,
+                    "chunks" => true,
+                    other => return Err(format!("Almacenamiento desconocido: {} (usa cubes o chunks)", other)),
+                }),
+                _ => return Err(format!("Opción desconocida: {}", flag)),
+            }
+        }
+
+        let seed = params.seed;
+        let kind = match name.as_str() {
+            "diorama" => SceneKind::Diorama,
+            "terrain" => SceneKind::Terrain(params),
+            _ => return Err(format!("Escena desconocida: {} (disponibles: diorama, terrain)", name)),
+        };
+
+        let structures = (structures > 0).then(|| PlacementParams {
+            seed,
+            count: structures,
+            kinds: StructureKind::ALL.to_vec(),
+            block_size: kind.grid(),
+        });
+
+        // Por defecto el terreno va en chunks; el diorama tiene cajas fuera de la cuadrícula
+        let chunked = storage.unwrap_or(matches!(kind, SceneKind::Terrain(_)));
+
+        Ok(SceneSpec { kind, structures, chunked })
+    }
+
+    // Nombre corto que identifica la escena generada (tipo, semilla, tamaño y estructuras),
+    // usado para los archivos que se guardan junto a ella
+    pub fn name(&self) -> String {
+        let mut name = match &self.kind {
+            SceneKind::Diorama => "diorama".to_string(),
+            SceneKind::Terrain(params) => format!("terrain-{}-{}x{}", params.seed, params.width, params.depth),
+        };
+        if let Some(params) = &self.structures {
+            name += &format!("-{}structures", params.count);
+        }
+        name
+    }
+
+    pub fn generate(&self) -> Vec<Cube> {
+        let mut objects = self.kind.generate();
+        if let Some(params) = &self.structures {
+            let placed = place_structures(&mut objects, params);
+            if placed < params.count {
+                println!("Solo se colocaron {} de {} estructuras", placed, params.count);
+            }
+        }
+        objects
+    }
+
+    pub fn validate(&self) -> Vec<Issue> {
+        validate_scene(&self.generate(), self.kind.grid())
+    }
+
+    // Genera la escena, la valida y fusiona los bloques repetidos antes de renderizar
+    pub fn load(&self) -> Scene {
+        let objects = self.generate();
+
+        let issues = validate_scene(&objects, self.kind.grid());
+        if !issues.is_empty() {
+            println!("La escena tiene {} problemas:", issues.len());
+            for issue in issues.iter().take(5) {
+                println!("  {}", issue);
+            }
+            if issues.len() > 5 {
+                println!("  ... (usa el subcomando `validate` para ver la lista completa)");
+            }
+        }
+
+        let objects = if self.chunked {
+            let world = World::from_cubes(&objects, self.kind.grid());
+            println!("Mundo con {} bloques en {} chunks", world.block_count(), world.chunk_count());
+            Geometry::Chunks(world)
+        } else {
+            Geometry::Cubes(merge_blocks(&objects))
+        };
+
+        let (camera, light_position) = self.kind.framing();
+        Scene { objects, camera, light_position }
+    }
+}
+
+impl SceneKind {
+    // Tamaño de bloque al que deberían estar alineados los cubos de la escena
+    pub fn grid(&self) -> f32 {
+        match self {
+            SceneKind::Diorama => BLOCK_SIZE,
+            SceneKind::Terrain(params) => params.block_size,
+        }
+    }
+
+    pub fn generate(&self) -> Vec<Cube> {
+        match self {
+            SceneKind::Diorama => generate_diorama(),
+            SceneKind::Terrain(params) => generate_terrain(params),
+        }
+    }
+
+    // Cámara y posición de la luz iniciales para la escena
+    pub fn framing(&self) -> (Camera, Vec3) {
+        match self {
+            SceneKind::Diorama => (
+                Camera::new(
+                    Vec3::new(0.0, 10.0, -10.0),  // Cambiamos la posición de la cámara (más alto y alejado)
+                    Vec3::new(0.0, 5.0, 0.0),     // Apuntamos hacia el centro del diorama
+                    Vec3::new(0.0, 1.0, 0.0),     // Vector "up", ajustado para la orientación
+                ),
+                Vec3::new(2.5, 3.0, 2.5),  // Ajusta la posición de la luz
+            ),
+            SceneKind::Terrain(params) => {
+                // Encuadramos el centro del terreno desde arriba y con la luz como un "sol" cercano
+                let size = params.block_size;
+                let center = Vec3::new(params.width as f32 * 0.5, params.water_level as f32, params.depth as f32 * 0.5) * size;
+                // En terrenos grandes nos quedamos cerca del centro para no pasar la distancia de visión
+                let extent = (params.width.max(params.depth) as f32 * size).min(12.0);
+                (
+                    Camera::new(center + Vec3::new(0.0, extent * 0.5, -extent * 0.6), center, Vec3::new(0.0, 1.0, 0.0)),
+                    center + Vec3::new(0.0, params.max_height as f32 * size + 2.0, 0.0),
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::framebuffer::Framebuffer;
+
+// Nombre libre para una captura: `screenshot_AAAAMMDD_HHMMSS.png` en hora UTC,
+// con un número al final si ya hay otra captura del mismo segundo
+pub fn screenshot_path(suffix: &str) -> String {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+    let time = seconds % 86_400;
+    let stem = format!(
+        "screenshot_{:04}{:02}{:02}_{:02}{:02}{:02}{}",
+        year, month, day, time / 3600, (time / 60) % 60, time % 60, suffix
+    );
+
+    let mut path = format!("{}.png", stem);
+    let mut counter = 2;
+    while Path::new(&path).exists() {
+        path = format!("{}_{}.png", stem, counter);
+        counter += 1;
+    }
+    path
+}
+
+// Guarda un buffer de píxeles `0xRRGGBB` (por ejemplo el ya escalado a la ventana) como PNG
+pub fn save_buffer(buffer: &[u32], width: usize, height: usize, path: &str) -> Result<(), String> {
+    let mut framebuffer = Framebuffer::new(width, height);
+    framebuffer.buffer.copy_from_slice(&buffer[..width * height]);
+    framebuffer.save(path).map_err(|e| format!("No se pudo guardar {}: {}", path, e))
+}
+
+// Días desde 1970-01-01 a fecha (año, mes, día), algoritmo de Howard Hinnant
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
@@ -0,0 +1,123 @@
+use crate::camera::Camera;
+use crate::config::Environment;
+use crate::framebuffer::Framebuffer;
+use crate::light::Light;
+use crate::ray_intersect::SceneIntersect;
+use crate::render;
+
+// Cómo se combinan los dos ojos en un solo framebuffer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StereoLayout {
+    SideBySide,  // Ojo izquierdo a la izquierda, derecho a la derecha
+    TopBottom,   // Ojo izquierdo arriba, derecho abajo
+    Anaglyph,    // Rojo del ojo izquierdo, verde y azul del derecho (lentes rojo/cian)
+}
+
+impl StereoLayout {
+    pub fn parse(name: &str) -> Result<StereoLayout, String> {
+        match name {
+            "side-by-side" => Ok(StereoLayout::SideBySide),
+            "top-bottom" => Ok(StereoLayout::TopBottom),
+            "anaglyph" => Ok(StereoLayout::Anaglyph),
+            _ => Err(format!("Modo estéreo desconocido: {} (usa side-by-side, top-bottom o anaglyph)", name)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StereoParams {
+    pub layout: StereoLayout,
+    pub eye_separation: f32,  // Distancia entre los ojos, en unidades del mundo
+    pub convergence: f32,     // Distancia a la que se cruzan las miradas (ahí la imagen queda sin paralaje)
+}
+
+impl StereoParams {
+    // Separación exagerada respecto a la de una persona para que el diorama se vea como maqueta
+    pub fn new(layout: StereoLayout, convergence: f32) -> Self {
+        StereoParams { layout, eye_separation: 0.3, convergence }
+    }
+
+    // Cámaras de los ojos izquierdo y derecho, separadas a los lados de `camera.eye`
+    // y mirando al punto de convergencia sobre la dirección de la vista
+    pub fn eyes(&self, camera: &Camera) -> (Camera, Camera) {
+        let forward = (camera.center - camera.eye).normalize();
+        let right = forward.cross(&camera.up).normalize();
+        let focus = camera.eye + forward * self.convergence;
+
+        let eye = |side: f32| {
+            let eye = camera.eye + right * (side * self.eye_separation * 0.5);
+            let mut eye_camera = Camera::new(eye, focus, camera.up);
+            eye_camera.fov = camera.fov;
+            eye_camera.projection = camera.projection;
+            eye_camera.aperture = camera.aperture;
+            eye_camera.focus_distance = camera.focus_distance;
+            eye_camera
+        };
+
+        (eye(-1.0), eye(1.0))
+    }
+}
+
+/// Renderiza los dos ojos y los combina en `framebuffer` según el modo elegido.
+/// En side-by-side y top-bottom cada ojo ocupa la mitad de la imagen con su propia proporción,
+/// así que el lado que se parte tiene que medir al menos 2 píxeles.
+pub fn render_stereo<S: SceneIntersect + Sync + ?Sized>(framebuffer: &mut Framebuffer, objects: &S, camera: &Camera, light: &Light, environment: &Environment, params: &StereoParams) -> Result<(), String> {
+    let (left_camera, right_camera) = params.eyes(camera);
+    let (width, height) = (framebuffer.width, framebuffer.height);
+
+    let (eye_width, eye_height) = match params.layout {
+        StereoLayout::SideBySide => (width / 2, height),
+        StereoLayout::TopBottom => (width, height / 2),
+        StereoLayout::Anaglyph => (width, height),
+    };
+    if eye_width == 0 || eye_height == 0 {
+        return Err(format!("La imagen de {}x{} es muy chica para partirla entre los dos ojos", width, height));
+    }
+
+    let mut left = Framebuffer::new(eye_width, eye_height);
+    let mut right = Framebuffer::new(eye_width, eye_height);
+    render(&mut left, objects, &left_camera, light, environment);
+    render(&mut right, objects, &right_camera, light, environment);
+
+    for y in 0..height {
+        for x in 0..width {
+            let pixel = match params.layout {
+                StereoLayout::SideBySide if x < eye_width => left.buffer[y * eye_width + x],
+                StereoLayout::SideBySide => right.buffer[y * eye_width + (x - eye_width).min(eye_width - 1)],
+                StereoLayout::TopBottom if y < eye_height => left.buffer[y * eye_width + x],
+                StereoLayout::TopBottom => right.buffer[(y - eye_height).min(eye_height - 1) * eye_width + x],
+                StereoLayout::Anaglyph => {
+                    let index = y * width + x;
+                    (left.buffer[index] & 0xFF0000) | (right.buffer[index] & 0x00FFFF)
+                }
+            };
+            framebuffer.buffer[y * width + x] = pixel;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::cube::Cube;
+    use nalgebra_glm::Vec3;
+
+    #[test]
+    fn split_side_needs_two_pixels() {
+        let camera = Camera::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        let light = Light::new(Vec3::new(0.0, 5.0, 5.0), Color::new(255, 255, 255), 1.0);
+        let objects: &[Cube] = &[];
+        let render = |layout, width, height| {
+            let mut framebuffer = Framebuffer::new(width, height);
+            render_stereo(&mut framebuffer, objects, &camera, &light, &Environment::default(), &StereoParams::new(layout, 5.0))
+        };
+
+        assert!(render(StereoLayout::SideBySide, 1, 10).is_err());
+        assert!(render(StereoLayout::TopBottom, 10, 1).is_err());
+        assert!(render(StereoLayout::SideBySide, 2, 1).is_ok());
+        assert!(render(StereoLayout::TopBottom, 3, 3).is_ok());
+        assert!(render(StereoLayout::Anaglyph, 1, 1).is_ok());
+    }
+}
@@ -0,0 +1,415 @@
+use nalgebra_glm::Vec3;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{BTreeMap, HashSet};
+use crate::cube::Cube;
+use crate::material::Material;
+
+// Celda entera dentro de la cuadrícula de bloques
+type Cell = (i32, i32, i32);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StructureKind {
+    Oak,
+    Birch,
+    Azalea,
+    Bamboo,
+    Boulder,
+    Hut,
+}
+
+impl StructureKind {
+    pub const ALL: [StructureKind; 6] = [
+        StructureKind::Oak,
+        StructureKind::Birch,
+        StructureKind::Azalea,
+        StructureKind::Bamboo,
+        StructureKind::Boulder,
+        StructureKind::Hut,
+    ];
+
+    // Las casas necesitan suelo debajo de todo su piso; el resto solo bajo su base
+    fn needs_flat_ground(&self) -> bool {
+        matches!(self, StructureKind::Hut)
+    }
+}
+
+// Tipos de bloque que usan las estructuras, resueltos contra la paleta al colocarlas
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Block {
+    OakLog,
+    OakLeaves,
+    BirchLog,
+    BirchLeaves,
+    Azalea,
+    Bamboo,
+    Stone,
+    Mossy,
+    Bricks,
+    Glass,
+    Roof,
+}
+
+// Materiales de las estructuras, cargados una sola vez
+struct Palette {
+    oak_log: Material,
+    oak_leaves: Material,
+    birch_log: Material,
+    birch_leaves: Material,
+    azalea: Material,
+    bamboo: Material,
+    stone: Material,
+    mossy: Material,
+    bricks: Material,
+    glass: Material,
+    roof: Material,
+}
+
+impl Palette {
+    fn load() -> Self {
+        Palette {
+            oak_log: Material::oak_log(),
+            oak_leaves: Material::oak_leaves(),
+            birch_log: Material::birch_log(),
+            birch_leaves: Material::birch_leaves(),
+            azalea: Material::azalea(),
+            bamboo: Material::bamboo(),
+            stone: Material::stone(),
+            mossy: Material::moosy(),
+            bricks: Material::moosy_block(),
+            glass: Material::glass(),
+            roof: Material::terracota(),
+        }
+    }
+
+    fn get(&self, block: Block) -> &Material {
+        match block {
+            Block::OakLog => &self.oak_log,
+            Block::OakLeaves => &self.oak_leaves,
+            Block::BirchLog => &self.birch_log,
+            Block::BirchLeaves => &self.birch_leaves,
+            Block::Azalea => &self.azalea,
+            Block::Bamboo => &self.bamboo,
+            Block::Stone => &self.stone,
+            Block::Mossy => &self.mossy,
+            Block::Bricks => &self.bricks,
+            Block::Glass => &self.glass,
+            Block::Roof => &self.roof,
+        }
+    }
+}
+
+/// Estructura generada: bloques con coordenadas relativas a su base (0, 0, 0),
+/// que es la celda que queda justo encima del suelo.
+#[derive(Debug, Clone)]
+pub struct Structure {
+    pub kind: StructureKind,
+    blocks: Vec<(Cell, Block)>,
+}
+
+impl Structure {
+    // Genera una variante de la estructura; el generador decide alturas y radios
+    pub fn generate(kind: StructureKind, rng: &mut StdRng) -> Self {
+        let mut blocks = Vec::new();
+
+        match kind {
+            StructureKind::Oak => {
+                let height = rng.gen_range(4..=6);
+                canopy(&mut blocks, height - 2, 2, 2, Block::OakLeaves, rng);
+                canopy(&mut blocks, height, 2, 1, Block::OakLeaves, rng);
+                trunk(&mut blocks, (0, 0), height, Block::OakLog);
+            }
+            StructureKind::Birch => {
+                let height = rng.gen_range(5..=7);
+                canopy(&mut blocks, height - 3, 2, 1, Block::BirchLeaves, rng);
+                canopy(&mut blocks, height - 1, 2, 1, Block::BirchLeaves, rng);
+                blocks.push(((0, height + 1, 0), Block::BirchLeaves));
+                trunk(&mut blocks, (0, 0), height, Block::BirchLog);
+            }
+            StructureKind::Azalea => {
+                let height = rng.gen_range(2..=3);
+                canopy(&mut blocks, height, 2, 2, Block::Azalea, rng);
+                canopy(&mut blocks, height + 2, 1, 1, Block::Azalea, rng);
+                trunk(&mut blocks, (0, 0), height, Block::OakLog);
+            }
+            StructureKind::Bamboo => {
+                // Uno a tres tallos de bambú de distintas alturas alrededor de la base
+                let stalks = rng.gen_range(1..=3);
+                for (x, z) in [(0, 0), (1, 0), (0, 1)].into_iter().take(stalks) {
+                    let height = rng.gen_range(4..=8);
+                    trunk(&mut blocks, (x, z), height, Block::Bamboo);
+                }
+            }
+            StructureKind::Boulder => {
+                let radius: i32 = rng.gen_range(1..=2);
+                for x in -radius..=radius {
+                    for y in 0..=radius {
+                        for z in -radius..=radius {
+                            if x * x + y * y * 2 + z * z <= radius * radius + 1 {
+                                let block = if rng.gen_bool(0.3) { Block::Mossy } else { Block::Stone };
+                                blocks.push(((x, y, z), block));
+                            }
+                        }
+                    }
+                }
+            }
+            StructureKind::Hut => {
+                // Casa de 5x5 con puerta al frente (-Z), ventanas laterales y techo escalonado
+                for x in -2..=2i32 {
+                    for z in -2..=2i32 {
+                        let wall = x.abs() == 2 || z.abs() == 2;
+                        for y in 0..3 {
+                            let door = x == 0 && z == -2 && y < 2;
+                            let window = y == 1 && z == 0 && x.abs() == 2;
+                            if window {
+                                blocks.push(((x, y, z), Block::Glass));
+                            } else if wall && !door {
+                                blocks.push(((x, y, z), Block::Bricks));
+                            }
+                        }
+                        blocks.push(((x, 3, z), Block::Roof));
+                        if x.abs() <= 1 && z.abs() <= 1 {
+                            blocks.push(((x, 4, z), Block::Roof));
+                        }
+                    }
+                }
+            }
+        }
+
+        // Si dos bloques caen en la misma celda gana el último (el tronco sobre las hojas)
+        let mut seen = HashSet::new();
+        blocks.reverse();
+        blocks.retain(|&(cell, _)| seen.insert(cell));
+        blocks.reverse();
+
+        Structure { kind, blocks }
+    }
+
+    // Gira la estructura en cuartos de vuelta alrededor del eje Y y opcionalmente la refleja en X
+    pub fn transformed(&self, quarter_turns: u8, mirror: bool) -> Self {
+        let blocks = self.blocks.iter().map(|&((x, y, z), block)| {
+            let x = if mirror { -x } else { x };
+            let (x, z) = match quarter_turns % 4 {
+                0 => (x, z),
+                1 => (-z, x),
+                2 => (-x, -z),
+                _ => (z, -x),
+            };
+            ((x, y, z), block)
+        }).collect();
+
+        Structure { kind: self.kind, blocks }
+    }
+
+    // Celdas del mundo que ocuparía la estructura con la base en `base`
+    fn cells(&self, base: Cell) -> impl Iterator<Item = (Cell, Block)> + '_ {
+        self.blocks.iter().map(move |&((x, y, z), block)| ((base.0 + x, base.1 + y, base.2 + z), block))
+    }
+}
+
+// Columna de bloques desde y = 0 hasta `height - 1`
+fn trunk(blocks: &mut Vec<(Cell, Block)>, (x, z): (i32, i32), height: i32, block: Block) {
+    for y in 0..height {
+        blocks.push(((x, y, z), block));
+    }
+}
+
+// Capas de hojas cuadradas desde `bottom`, sin las esquinas exteriores (algunas al azar)
+fn canopy(blocks: &mut Vec<(Cell, Block)>, bottom: i32, layers: i32, radius: i32, block: Block, rng: &mut StdRng) {
+    for y in bottom..bottom + layers {
+        for x in -radius..=radius {
+            for z in -radius..=radius {
+                let corner = x.abs() == radius && z.abs() == radius;
+                if corner && (radius > 1 || rng.gen_bool(0.5)) {
+                    continue;
+                }
+                blocks.push(((x, y, z), block));
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlacementParams {
+    pub seed: u64,
+    pub count: usize,               // Cuántas estructuras intentar colocar
+    pub kinds: Vec<StructureKind>,  // Tipos entre los que se elige al azar
+    pub block_size: f32,
+}
+
+/// Reparte estructuras sobre la superficie de una escena existente.
+/// Cada estructura se gira y refleja al azar y solo se coloca si no choca con
+/// ningún cubo (ni con las estructuras ya colocadas). Devuelve cuántas se colocaron.
+pub fn place_structures(objects: &mut Vec<Cube>, params: &PlacementParams) -> usize {
+    if params.kinds.is_empty() || params.count == 0 {
+        return 0;
+    }
+
+    let size = params.block_size;
+    let mut occupied = occupied_cells(objects, size);
+
+    // Altura de la superficie de cada columna; BTreeMap para que el orden sea reproducible
+    let mut surface: BTreeMap<(i32, i32), i32> = BTreeMap::new();
+    for &(x, y, z) in &occupied {
+        let top = surface.entry((x, z)).or_insert(y);
+        *top = (*top).max(y);
+    }
+    let columns: Vec<((i32, i32), i32)> = surface.into_iter().collect();
+    if columns.is_empty() {
+        return 0;
+    }
+
+    let palette = Palette::load();
+    let mut rng = StdRng::seed_from_u64(params.seed);
+    let mut placed = 0;
+
+    for _ in 0..params.count * 10 {
+        if placed == params.count {
+            break;
+        }
+
+        let kind = params.kinds[rng.gen_range(0..params.kinds.len())];
+        let structure = Structure::generate(kind, &mut rng)
+            .transformed(rng.gen_range(0..4), rng.gen_bool(0.5));
+        let ((x, z), top) = columns[rng.gen_range(0..columns.len())];
+        let base = (x, top + 1, z);
+
+        // Comprobamos colisiones y que haya suelo debajo
+        let collides = structure.cells(base).any(|(cell, _)| occupied.contains(&cell));
+        let supported = structure.cells(base)
+            .filter(|&((cx, cy, cz), _)| cy == base.1 && (kind.needs_flat_ground() || (cx, cz) == (x, z)))
+            .all(|((cx, cy, cz), _)| occupied.contains(&(cx, cy - 1, cz)));
+        if collides || !supported {
+            continue;
+        }
+
+        for ((cx, cy, cz), block) in structure.cells(base) {
+            occupied.insert((cx, cy, cz));
+            objects.push(Cube {
+                min: Vec3::new(cx as f32, cy as f32, cz as f32) * size,
+                max: Vec3::new((cx + 1) as f32, (cy + 1) as f32, (cz + 1) as f32) * size,
+                material: palette.get(block).clone(),
+                uv_tile: None,
+            });
+        }
+        placed += 1;
+    }
+
+    placed
+}
+
+// Celdas de la cuadrícula que tocan los cubos de la escena (las cajas invertidas se normalizan)
+fn occupied_cells(objects: &[Cube], size: f32) -> HashSet<Cell> {
+    let mut cells = HashSet::new();
+
+    for cube in objects {
+        let min = cube.min.zip_map(&cube.max, f32::min) / size;
+        let max = cube.min.zip_map(&cube.max, f32::max) / size;
+        let extent = max - min;
+        if extent.x <= 0.0 || extent.y <= 0.0 || extent.z <= 0.0 {
+            continue;
+        }
+
+        let lo = |v: f32| (v + 1e-4).floor() as i32;
+        let hi = |v: f32| (v - 1e-4).ceil() as i32;
+        for x in lo(min.x)..hi(max.x) {
+            for y in lo(min.y)..hi(max.y) {
+                for z in lo(min.z)..hi(max.z) {
+                    cells.insert((x, y, z));
+                }
+            }
+        }
+    }
+
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::{generate_terrain, TerrainParams};
+    use crate::validate::validate_scene;
+
+    fn floor(size: f32, holes: &[(i32, i32)]) -> Vec<Cube> {
+        let mut objects = Vec::new();
+        for x in 0..12 {
+            for z in 0..12 {
+                if !holes.contains(&(x, z)) {
+                    let min = Vec3::new(x as f32, 0.0, z as f32) * size;
+                    objects.push(Cube { min, max: min + Vec3::new(size, size, size), material: Material::stone(), uv_tile: None });
+                }
+            }
+        }
+        objects
+    }
+
+    fn params(seed: u64, count: usize, kinds: Vec<StructureKind>) -> PlacementParams {
+        PlacementParams { seed, count, kinds, block_size: 0.5 }
+    }
+
+    #[test]
+    fn placement_is_reproducible_and_collision_free() {
+        let terrain = generate_terrain(&TerrainParams { seed: 3, width: 16, depth: 16, ..TerrainParams::default() });
+        let placement = params(9, 6, StructureKind::ALL.to_vec());
+
+        let mut a = terrain.clone();
+        let mut b = terrain.clone();
+        let placed = place_structures(&mut a, &placement);
+        assert_eq!(placed, place_structures(&mut b, &placement));
+        assert!(placed > 0);
+        assert_eq!(a, b);
+
+        // Ni duplicados ni superposiciones, ni con el terreno ni entre estructuras
+        assert!(validate_scene(&a, 0.5).is_empty());
+    }
+
+    #[test]
+    fn huts_stand_on_flat_ground() {
+        let holes = [(3, 3), (8, 5), (5, 9)];
+        let mut objects = floor(0.5, &holes);
+        let ground = objects.len();
+        assert!(place_structures(&mut objects, &params(1, 4, vec![StructureKind::Hut])) > 0);
+
+        // Todo bloque de la primera capa tiene suelo justo debajo
+        for cube in &objects[ground..] {
+            let (x, y, z) = ((cube.min.x / 0.5) as i32, (cube.min.y / 0.5) as i32, (cube.min.z / 0.5) as i32);
+            if y == 1 {
+                assert!((0..12).contains(&x) && (0..12).contains(&z) && !holes.contains(&(x, z)), "bloque sin suelo en ({}, {})", x, z);
+            }
+        }
+    }
+
+    #[test]
+    fn transformed_is_a_rotation_or_reflection() {
+        let mut rng = StdRng::seed_from_u64(5);
+        for kind in StructureKind::ALL {
+            let structure = Structure::generate(kind, &mut rng);
+            let cells: Vec<Cell> = structure.blocks.iter().map(|&(cell, _)| cell).collect();
+            // Producto vectorial en XZ: cambia de signo solo con una reflexión
+            let cross = |a: Cell, b: Cell, c: Cell| (b.0 - a.0) * (c.2 - a.2) - (b.2 - a.2) * (c.0 - a.0);
+
+            for turns in 0..4 {
+                for mirror in [false, true] {
+                    let moved = structure.transformed(turns, mirror);
+                    let moved: Vec<Cell> = moved.blocks.iter().map(|&(cell, _)| cell).collect();
+                    assert_eq!(moved.iter().collect::<HashSet<_>>().len(), cells.len());
+
+                    for (i, (a, a2)) in cells.iter().zip(&moved).enumerate() {
+                        assert_eq!(a.1, a2.1);
+                        assert_eq!(a.0 * a.0 + a.2 * a.2, a2.0 * a2.0 + a2.2 * a2.2);
+                        for (b, b2) in cells.iter().zip(&moved).skip(i + 1).take(8) {
+                            for (c, c2) in cells.iter().zip(&moved).skip(i + 2).take(8) {
+                                let sign = if mirror { -1 } else { 1 };
+                                assert_eq!(cross(*a, *b, *c) * sign, cross(*a2, *b2, *c2));
+                            }
+                        }
+                    }
+                }
+            }
+
+            // Cuatro cuartos de vuelta dejan la estructura como estaba
+            let turned = (0..4).fold(structure.clone(), |s, _| s.transformed(1, false));
+            assert_eq!(turned.blocks, structure.blocks);
+        }
+    }
+}
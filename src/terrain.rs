@@ -0,0 +1,146 @@
+use nalgebra_glm::Vec3;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::constants::BLOCK_SIZE;
+use crate::cube::Cube;
+use crate::material::Material;
+use crate::noise::Perlin;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerrainParams {
+    pub seed: u64,
+    pub width: usize,         // Bloques en X
+    pub depth: usize,         // Bloques en Z
+    pub max_height: usize,    // Altura máxima de la superficie en bloques
+    pub water_level: usize,   // Las columnas a esta altura o menos se cubren de arena
+    pub block_size: f32,
+    pub frequency: f32,       // Escala horizontal del relieve (más alto = colinas más pequeñas)
+    pub cave_threshold: f32,  // Valor del ruido 3D a partir del cual se excava una cueva
+    pub diamond_chance: f32,  // Probabilidad de diamante por bloque de piedra profundo
+    pub emerald_chance: f32,  // Probabilidad de esmeralda por bloque de piedra
+}
+
+impl Default for TerrainParams {
+    fn default() -> Self {
+        TerrainParams {
+            seed: 0,
+            width: 24,
+            depth: 24,
+            max_height: 12,
+            water_level: 4,
+            block_size: BLOCK_SIZE,
+            frequency: 0.06,
+            cave_threshold: 0.35,
+            diamond_chance: 0.02,
+            emerald_chance: 0.01,
+        }
+    }
+}
+
+// Materiales del terreno, cargados una sola vez y clonados para cada bloque
+struct Palette {
+    grass: Material,
+    dirt: Material,
+    stone: Material,
+    sand: Material,
+    diamond: Material,
+    emerald: Material,
+}
+
+/// Genera un terreno por mapa de alturas a partir de ruido de Perlin sembrado.
+/// La misma semilla y dimensiones producen siempre los mismos cubos.
+pub fn generate_terrain(params: &TerrainParams) -> Vec<Cube> {
+    let palette = Palette {
+        grass: Material::grass(),
+        dirt: Material::dirt(),
+        stone: Material::stone(),
+        sand: Material::sand(),
+        diamond: Material::diamond(),
+        emerald: Material::emerald(),
+    };
+
+    // Cada capa de ruido usa su propia semilla derivada para que no se correlacionen
+    let height_noise = Perlin::new(params.seed);
+    let cave_noise = Perlin::new(params.seed.wrapping_add(1));
+    let mut ore_rng = StdRng::seed_from_u64(params.seed.wrapping_add(2));
+
+    let size = params.block_size;
+    let mut objects = Vec::new();
+
+    for x in 0..params.width {
+        for z in 0..params.depth {
+            let n = height_noise.fbm2(x as f32 * params.frequency, z as f32 * params.frequency, 4);
+            let height = ((n * 0.5 + 0.5) * params.max_height as f32).round().clamp(1.0, params.max_height as f32) as usize;
+            let beach = height <= params.water_level + 1;
+
+            for y in 0..height {
+                let depth_below = height - 1 - y;
+
+                // Las cuevas no tocan la capa inferior ni la superficie
+                if y > 0 && depth_below > 0 {
+                    let c = cave_noise.noise3(x as f32 * 0.15, y as f32 * 0.2, z as f32 * 0.15);
+                    if c > params.cave_threshold {
+                        continue;
+                    }
+                }
+
+                let material = if depth_below == 0 {
+                    if beach { &palette.sand } else { &palette.grass }
+                } else if depth_below <= 3 {
+                    if beach { &palette.sand } else { &palette.dirt }
+                } else {
+                    let roll: f32 = ore_rng.gen();
+                    if y <= params.max_height / 4 && roll < params.diamond_chance {
+                        &palette.diamond
+                    } else if roll > 1.0 - params.emerald_chance {
+                        &palette.emerald
+                    } else {
+                        &palette.stone
+                    }
+                };
+
+                objects.push(Cube {
+                    min: Vec3::new(x as f32, y as f32, z as f32) * size,
+                    max: Vec3::new((x + 1) as f32, (y + 1) as f32, (z + 1) as f32) * size,
+                    material: material.clone(),
+                    uv_tile: None,
+                });
+            }
+        }
+    }
+
+    objects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::validate_scene;
+
+    fn small(seed: u64) -> TerrainParams {
+        TerrainParams { seed, width: 12, depth: 10, ..TerrainParams::default() }
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_terrain() {
+        assert_eq!(generate_terrain(&small(42)), generate_terrain(&small(42)));
+        assert_ne!(generate_terrain(&small(42)), generate_terrain(&small(43)));
+    }
+
+    #[test]
+    fn terrain_is_valid_and_stays_in_bounds() {
+        let params = small(7);
+        let objects = generate_terrain(&params);
+        assert!(validate_scene(&objects, params.block_size).is_empty());
+
+        let limit = Vec3::new(params.width as f32, params.max_height as f32, params.depth as f32) * params.block_size;
+        assert!(objects.iter().all(|cube| (0..3).all(|i| cube.min[i] >= 0.0 && cube.max[i] <= limit[i])));
+        // Toda columna tiene al menos el bloque del fondo
+        for x in 0..params.width {
+            for z in 0..params.depth {
+                let bottom = Vec3::new(x as f32, 0.0, z as f32) * params.block_size;
+                assert!(objects.iter().any(|cube| cube.min == bottom), "columna ({}, {}) vacía", x, z);
+            }
+        }
+    }
+}
@@ -2,14 +2,14 @@ use nalgebra_glm::Vec3;
 use crate::color::Color;
 
 pub fn load_texture(file_path: &str) -> (Vec<u8>, u32, u32) {
-    let img = image::open(file_path).expect(&format!("Error al cargar la textura: {}", file_path));
+    let img = image::open(file_path).unwrap_or_else(|_| panic!("Error al cargar la textura: {}", file_path));
     let img = img.to_rgba8();
     let (width, height) = img.dimensions();
         
     (img.into_raw(), width, height)  // Convertimos la imagen en un vector de bytes RGBA
 }
 
-pub fn get_texture_color(texture: &Vec<u8>, texture_width: u32, texture_height: u32, uv: [f32; 2]) -> Color {
+pub fn get_texture_color(texture: &[u8], texture_width: u32, texture_height: u32, uv: [f32; 2]) -> Color {
     let tex_x = (uv[0] * texture_width as f32) as usize;
     let tex_y = (uv[1] * texture_height as f32) as usize;
     let pixel_index = (tex_y * texture_width as usize + tex_x) * 4;  // Formato RGBA, 4 bytes por píxel
@@ -26,24 +26,51 @@ pub fn get_texture_color(texture: &Vec<u8>, texture_width: u32, texture_height:
 }
 
 
-pub fn calculate_uv(point_on_cube: Vec3, normal: Vec3, min: Vec3, max: Vec3) -> [f32; 2] {
+pub fn calculate_uv(point_on_cube: Vec3, normal: Vec3, min: Vec3, max: Vec3, uv_tile: Option<f32>) -> [f32; 2] {
+    // Sin tamaño de bloque la textura cubre toda la cara; con él se repite cada `tile` unidades.
+    // En ambos casos el borde de la cara (1.0) vuelve a 0.0, igual que entre dos bloques vecinos
+    let span = |p: f32, lo: f32, hi: f32| {
+        let t = match uv_tile {
+            Some(tile) => (p - lo) / tile,
+            None => (p - lo) / (hi - lo),
+        };
+        t - t.floor()
+    };
+
     let (u, v) = if normal.z.abs() > 0.5 {
         // Cara frontal o trasera, usamos X e Y
-        let u = (point_on_cube.x - min.x) / (max.x - min.x);
-        let v = (point_on_cube.y - min.y) / (max.y - min.y);
-        (u, v)
+        (span(point_on_cube.x, min.x, max.x), span(point_on_cube.y, min.y, max.y))
     } else if normal.x.abs() > 0.5 {
         // Caras laterales, usamos Z e Y
-        let u = (point_on_cube.z - min.z) / (max.z - min.z);
-        let v = (point_on_cube.y - min.y) / (max.y - min.y);
-        (u, v)
+        (span(point_on_cube.z, min.z, max.z), span(point_on_cube.y, min.y, max.y))
     } else {
         // Cara superior o inferior, usamos X y Z
-        let u = (point_on_cube.x - min.x) / (max.x - min.x);
-        let v = (point_on_cube.z - min.z) / (max.z - min.z);
-        (u, v)
+        (span(point_on_cube.x, min.x, max.x), span(point_on_cube.z, min.z, max.z))
     };
 
     // Nos aseguramos de que las coordenadas UV estén entre 0.0 y 1.0
     [u.clamp(0.0, 1.0), v.clamp(0.0, 1.0)]
 }
+
+// Desplaza las coordenadas de una textura animada `scroll` texturas por segundo, repitiéndola
+pub fn scroll_uv(uv: [f32; 2], scroll: [f32; 2], time: f32) -> [f32; 2] {
+    let wrap = |t: f32| t - t.floor();
+    [wrap(uv[0] + scroll[0] * time), wrap(uv[1] + scroll[1] * time)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uv_repeats_once_per_tile() {
+        let normal = Vec3::new(0.0, 1.0, 0.0);
+        let (min, max) = (Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.5, 0.5, 1.0));
+        // En una caja de tres bloques el segundo empieza de nuevo en 0.0
+        assert_eq!(calculate_uv(Vec3::new(0.75, 0.5, 0.25), normal, min, max, Some(0.5)), [0.5, 0.5]);
+        // El borde entre bloques y el borde de la cara vuelven a 0.0, igual que en un bloque suelto
+        assert_eq!(calculate_uv(Vec3::new(0.5, 0.5, 0.0), normal, min, max, Some(0.5)), [0.0, 0.0]);
+        assert_eq!(calculate_uv(Vec3::new(1.5, 0.5, 1.0), normal, min, max, Some(0.5)), [0.0, 0.0]);
+        assert_eq!(calculate_uv(Vec3::new(0.5, 0.5, 0.5), normal, min, Vec3::new(0.5, 0.5, 0.5), None), [0.0, 0.0]);
+    }
+}
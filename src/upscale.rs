@@ -0,0 +1,278 @@
+use rayon::prelude::*;
+use std::f32::consts::PI;
+use crate::framebuffer::Framebuffer;
+
+// Filtro para llevar el framebuffer reducido al tamaño de la ventana
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UpscaleFilter {
+    Nearest,   // Copia el píxel más cercano (bloques nítidos, bordes en escalera)
+    Bilinear,  // Mezcla los 2 x 2 píxeles vecinos
+    Bicubic,   // Catmull-Rom sobre 4 x 4 píxeles, más nítido que el bilineal
+    Lanczos,   // Lanczos de 3 lóbulos sobre 6 x 6 píxeles, el más nítido y el más caro
+}
+
+impl UpscaleFilter {
+    pub const ALL: [UpscaleFilter; 4] = [UpscaleFilter::Nearest, UpscaleFilter::Bilinear, UpscaleFilter::Bicubic, UpscaleFilter::Lanczos];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            UpscaleFilter::Nearest => "nearest",
+            UpscaleFilter::Bilinear => "bilinear",
+            UpscaleFilter::Bicubic => "bicubic",
+            UpscaleFilter::Lanczos => "lanczos",
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<UpscaleFilter, String> {
+        UpscaleFilter::ALL.into_iter().find(|filter| filter.name() == name)
+            .ok_or(format!("Filtro de escalado desconocido: {} (usa nearest, bilinear, bicubic o lanczos)", name))
+    }
+
+    // El siguiente filtro, para pasar por todos con una tecla
+    pub fn next(&self) -> UpscaleFilter {
+        let index = UpscaleFilter::ALL.iter().position(|filter| filter == self).unwrap();
+        UpscaleFilter::ALL[(index + 1) % UpscaleFilter::ALL.len()]
+    }
+
+    fn radius(&self) -> f32 {
+        match self {
+            UpscaleFilter::Nearest => 0.5,
+            UpscaleFilter::Bilinear => 1.0,
+            UpscaleFilter::Bicubic => 2.0,
+            UpscaleFilter::Lanczos => 3.0,
+        }
+    }
+
+    // Peso de un píxel a `x` píxeles de distancia del punto muestreado
+    fn kernel(&self, x: f32) -> f32 {
+        let x = x.abs();
+        match self {
+            UpscaleFilter::Nearest => if x < 0.5 { 1.0 } else { 0.0 },
+            UpscaleFilter::Bilinear => (1.0 - x).max(0.0),
+            UpscaleFilter::Bicubic => {
+                if x < 1.0 {
+                    1.5 * x.powi(3) - 2.5 * x * x + 1.0
+                } else if x < 2.0 {
+                    -0.5 * x.powi(3) + 2.5 * x * x - 4.0 * x + 2.0
+                } else {
+                    0.0
+                }
+            }
+            UpscaleFilter::Lanczos => {
+                if x < 1e-6 {
+                    1.0
+                } else if x < 3.0 {
+                    let px = PI * x;
+                    3.0 * px.sin() * (px / 3.0).sin() / (px * px)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// Escala el framebuffer reducido al tamaño de la ventana con el filtro elegido, repartiendo las filas entre hilos.
+/// Los filtros se aplican por separado en horizontal y en vertical.
+pub fn upscale_framebuffer(framebuffer: &Framebuffer, target_width: usize, target_height: usize, filter: UpscaleFilter) -> Vec<u32> {
+    let mut scaled_buffer = vec![0; target_width * target_height];
+
+    if filter == UpscaleFilter::Nearest {
+        let x_ratio = framebuffer.width as f32 / target_width as f32;
+        let y_ratio = framebuffer.height as f32 / target_height as f32;
+
+        scaled_buffer.par_chunks_mut(target_width).enumerate().for_each(|(y, row)| {
+            let src_y = (y as f32 * y_ratio).floor() as usize;
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let src_x = (x as f32 * x_ratio).floor() as usize;
+                *pixel = framebuffer.buffer[src_y * framebuffer.width + src_x];
+            }
+        });
+        return scaled_buffer;
+    }
+
+    let columns = taps(framebuffer.width, target_width, filter);
+    let rows = taps(framebuffer.height, target_height, filter);
+
+    // Primera pasada: cada fila del framebuffer se estira al ancho de la ventana
+    let mut horizontal = vec![[0.0f32; 3]; framebuffer.height * target_width];
+    horizontal.par_chunks_mut(target_width).enumerate().for_each(|(y, row)| {
+        let source = &framebuffer.buffer[y * framebuffer.width..(y + 1) * framebuffer.width];
+        for (pixel, taps) in row.iter_mut().zip(&columns) {
+            for &(x, weight) in taps {
+                let channels = unpack(source[x]);
+                for c in 0..3 {
+                    pixel[c] += channels[c] * weight;
+                }
+            }
+        }
+    });
+
+    // Segunda pasada: las columnas se estiran al alto de la ventana
+    scaled_buffer.par_chunks_mut(target_width).enumerate().for_each(|(y, row)| {
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let mut sum = [0.0f32; 3];
+            for &(src_y, weight) in &rows[y] {
+                let value = horizontal[src_y * target_width + x];
+                for c in 0..3 {
+                    sum[c] += value[c] * weight;
+                }
+            }
+            *pixel = pack(sum);
+        }
+    });
+
+    scaled_buffer
+}
+
+// Píxeles de origen y pesos (ya normalizados) de cada píxel de destino en una dimensión.
+// Los píxeles fuera del borde repiten el del borde
+fn taps(source: usize, target: usize, filter: UpscaleFilter) -> Vec<Vec<(usize, f32)>> {
+    let ratio = source as f32 / target as f32;
+    // Al reducir se ensancha el filtro para no saltarse píxeles
+    let scale = ratio.max(1.0);
+    let radius = filter.radius() * scale;
+
+    (0..target).map(|i| {
+        let center = (i as f32 + 0.5) * ratio - 0.5;
+        let first = (center - radius).floor() as isize;
+        let last = (center + radius).ceil() as isize;
+
+        let mut taps: Vec<(usize, f32)> = (first..=last)
+            .map(|j| (j.clamp(0, source as isize - 1) as usize, filter.kernel((j as f32 - center) / scale)))
+            .filter(|&(_, weight)| weight != 0.0)
+            .collect();
+        let total: f32 = taps.iter().map(|&(_, weight)| weight).sum();
+        for tap in &mut taps {
+            tap.1 /= total;
+        }
+        taps
+    }).collect()
+}
+
+/// Realce de bordes adaptativo (parecido a CAS): cada píxel se separa de sus cuatro vecinos según `amount` (0..1),
+/// pero menos donde el contraste local ya es alto, para no crear halos alrededor de los bloques.
+pub fn sharpen(buffer: &mut [u32], width: usize, height: usize, amount: f32) {
+    if amount <= 0.0 {
+        return;
+    }
+    let source = buffer.to_vec();
+    // Peso negativo máximo de los vecinos: de -1/8 (suave) a -1/5 (fuerte)
+    let strength = -(0.125 + (0.2 - 0.125) * amount.min(1.0));
+
+    buffer.par_chunks_mut(width).enumerate().for_each(|(y, row)| {
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let at = |x: usize, y: usize| unpack(source[y * width + x]);
+            let center = at(x, y);
+            let neighbours = [
+                at(x.saturating_sub(1), y),
+                at((x + 1).min(width - 1), y),
+                at(x, y.saturating_sub(1)),
+                at(x, (y + 1).min(height - 1)),
+            ];
+
+            let mut result = [0.0; 3];
+            for c in 0..3 {
+                let min = neighbours.iter().fold(center[c], |m, n| m.min(n[c]));
+                let max = neighbours.iter().fold(center[c], |m, n| m.max(n[c]));
+                // Cuánto margen queda antes de recortar a 0 o a 255: poco margen, poco realce
+                let headroom = if max > 0.0 { (min.min(255.0 - max) / max).clamp(0.0, 1.0) } else { 0.0 };
+                let weight = strength * headroom.sqrt();
+                let sum: f32 = neighbours.iter().map(|n| n[c]).sum();
+                result[c] = (center[c] + weight * sum) / (1.0 + 4.0 * weight);
+            }
+            *pixel = pack(result);
+        }
+    });
+}
+
+fn unpack(hex: u32) -> [f32; 3] {
+    [((hex >> 16) & 0xFF) as f32, ((hex >> 8) & 0xFF) as f32, (hex & 0xFF) as f32]
+}
+
+fn pack(channels: [f32; 3]) -> u32 {
+    let channel = |v: f32| v.round().clamp(0.0, 255.0) as u32;
+    (channel(channels[0]) << 16) | (channel(channels[1]) << 8) | channel(channels[2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn constant(width: usize, height: usize, color: u32) -> Framebuffer {
+        let mut framebuffer = Framebuffer::new(width, height);
+        framebuffer.buffer.iter_mut().for_each(|pixel| *pixel = color);
+        framebuffer
+    }
+
+    #[test]
+    fn kernels_interpolate_and_have_negative_lobes() {
+        for filter in [UpscaleFilter::Bilinear, UpscaleFilter::Bicubic, UpscaleFilter::Lanczos] {
+            // Pasan por los píxeles de origen: 1 en el centro y 0 en los demás enteros
+            assert!((filter.kernel(0.0) - 1.0).abs() < 1e-6, "{:?}", filter);
+            for x in 1..=3 {
+                assert!(filter.kernel(x as f32).abs() < 1e-6, "{:?} en {}", filter, x);
+            }
+            assert_eq!(filter.kernel(0.7), filter.kernel(-0.7), "{:?}", filter);
+            assert_eq!(filter.kernel(filter.radius() + 0.01), 0.0, "{:?}", filter);
+        }
+        assert_eq!(UpscaleFilter::Bicubic.kernel(0.5), 0.5625);
+        assert!(UpscaleFilter::Bicubic.kernel(1.5) < 0.0);
+        assert!(UpscaleFilter::Lanczos.kernel(1.5) < 0.0);
+        assert!(UpscaleFilter::Lanczos.kernel(2.5) > 0.0);
+    }
+
+    #[test]
+    fn taps_are_normalized() {
+        // `Nearest` no usa taps: copia el píxel directamente
+        for filter in [UpscaleFilter::Bilinear, UpscaleFilter::Bicubic, UpscaleFilter::Lanczos] {
+            for (source, target) in [(10, 25), (25, 10), (7, 7)] {
+                for taps in taps(source, target, filter) {
+                    let total: f32 = taps.iter().map(|&(_, weight)| weight).sum();
+                    assert!((total - 1.0).abs() < 1e-5, "{:?} {} -> {}", filter, source, target);
+                    assert!(taps.iter().all(|&(x, _)| x < source));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn constant_image_stays_constant() {
+        let color = 0x40_80_C0;
+        for filter in UpscaleFilter::ALL {
+            for (width, height) in [(23, 17), (5, 3)] {
+                let scaled = upscale_framebuffer(&constant(10, 8, color), width, height, filter);
+                assert!(scaled.iter().all(|&pixel| pixel == color), "{:?} a {}x{}", filter, width, height);
+            }
+        }
+
+        let mut buffer = constant(6, 4, color).buffer;
+        sharpen(&mut buffer, 6, 4, 1.0);
+        assert!(buffer.iter().all(|&pixel| pixel == color));
+    }
+
+    #[test]
+    fn nearest_repeats_source_pixels() {
+        let mut framebuffer = Framebuffer::new(2, 1);
+        framebuffer.buffer = vec![0x000000, 0xFFFFFF];
+        assert_eq!(upscale_framebuffer(&framebuffer, 4, 2, UpscaleFilter::Nearest), [0, 0, 0xFFFFFF, 0xFFFFFF, 0, 0, 0xFFFFFF, 0xFFFFFF]);
+    }
+
+    #[test]
+    fn sharpen_raises_contrast_without_clipping() {
+        // Rampa gris suave: el realce aleja cada píxel del promedio de sus vecinos
+        let ramp = [64, 96, 128, 160, 192].map(|v: u32| v * 0x010101);
+        let mut buffer = ramp.to_vec();
+        buffer[2] = 150 * 0x010101;
+        let before = buffer.clone();
+        sharpen(&mut buffer, 5, 1, 1.0);
+
+        let gray = |pixel: u32| (pixel & 0xFF) as i32;
+        assert!(gray(buffer[2]) > gray(before[2]), "{} -> {}", gray(before[2]), gray(buffer[2]));
+        assert!(buffer.iter().all(|&pixel| gray(pixel) <= 255));
+        // Con `amount` 0 no cambia nada
+        let mut untouched = before.clone();
+        sharpen(&mut untouched, 5, 1, 0.0);
+        assert_eq!(untouched, before);
+    }
+}
@@ -0,0 +1,178 @@
+use std::fmt;
+use crate::cube::Cube;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IssueKind {
+    Inverted,                   // max < min en algún eje
+    ZeroVolume,                 // max == min en algún eje
+    Duplicate { other: usize }, // Mismas coordenadas que otro cubo
+    Overlap { other: usize },   // Comparte volumen con otro cubo
+    OffGrid,                    // Alguna esquina no cae sobre la cuadrícula de bloques
+    MissingTexture,             // El material no tiene ni textura válida ni color difuso
+}
+
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub index: usize,
+    pub cube: Cube,
+    pub kind: IssueKind,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let min = self.cube.min;
+        let max = self.cube.max;
+        write!(
+            f,
+            "cubo #{} [min ({}, {}, {}) max ({}, {}, {})]: ",
+            self.index, min.x, min.y, min.z, max.x, max.y, max.z
+        )?;
+
+        match &self.kind {
+            IssueKind::Inverted => write!(f, "caja invertida (max < min)"),
+            IssueKind::ZeroVolume => write!(f, "caja sin volumen"),
+            IssueKind::Duplicate { other } => write!(f, "duplicado del cubo #{}", other),
+            IssueKind::Overlap { other } => write!(f, "se superpone con el cubo #{}", other),
+            IssueKind::OffGrid => write!(f, "fuera de la cuadrícula de bloques"),
+            IssueKind::MissingTexture => write!(f, "material sin textura ni color"),
+        }
+    }
+}
+
+/// Revisa la escena y devuelve todos los problemas encontrados, ordenados por cubo.
+/// `grid` es el tamaño de bloque al que deberían estar alineadas las esquinas.
+pub fn validate_scene(objects: &[Cube], grid: f32) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let mut solid = Vec::new();  // Índices de las cajas con volumen, para buscar superposiciones
+
+    for (index, cube) in objects.iter().enumerate() {
+        let extent = cube.max - cube.min;
+        let mut push = |kind| issues.push(Issue { index, cube: cube.clone(), kind });
+
+        if extent.x < 0.0 || extent.y < 0.0 || extent.z < 0.0 {
+            push(IssueKind::Inverted);
+        } else if extent.x == 0.0 || extent.y == 0.0 || extent.z == 0.0 {
+            push(IssueKind::ZeroVolume);
+        } else {
+            solid.push(index);
+        }
+
+        let on_grid = |v: f32| (v / grid - (v / grid).round()).abs() < 1e-4;
+        if !cube.min.iter().chain(cube.max.iter()).all(|&v| on_grid(v)) {
+            push(IssueKind::OffGrid);
+        }
+
+        let material = &cube.material;
+        let texture_ok = material.texture.as_ref().is_some_and(|texture| {
+            texture.len() == (material.texture_width * material.texture_height * 4) as usize && !texture.is_empty()
+        });
+        if !texture_ok && material.diffuse.is_none() {
+            push(IssueKind::MissingTexture);
+        }
+    }
+
+    // Barrido por el eje X: solo comparamos cajas cuyos intervalos en X se cruzan
+    solid.sort_by(|&a, &b| objects[a].min.x.total_cmp(&objects[b].min.x));
+    for (i, &a) in solid.iter().enumerate() {
+        for &b in &solid[i + 1..] {
+            let (first, second) = (&objects[a], &objects[b]);
+            if second.min.x >= first.max.x {
+                break;
+            }
+
+            let overlaps = second.min.y < first.max.y && first.min.y < second.max.y
+                && second.min.z < first.max.z && first.min.z < second.max.z;
+            if !overlaps {
+                continue;
+            }
+
+            // El problema se reporta en el cubo que aparece después en la escena
+            let (index, other) = (a.max(b), a.min(b));
+            let kind = if first.min == second.min && first.max == second.max {
+                IssueKind::Duplicate { other }
+            } else {
+                IssueKind::Overlap { other }
+            };
+            issues.push(Issue { index, cube: objects[index].clone(), kind });
+        }
+    }
+
+    issues.sort_by_key(|issue| issue.index);
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra_glm::Vec3;
+    use crate::color::Color;
+    use crate::diorama::generate_diorama;
+    use crate::material::Material;
+
+    fn gray() -> Material {
+        Material::new("gris", Some(Color::new(128, 128, 128)), None, 0, 0, 0.0, [1.0, 0.0], None, 0.0)
+    }
+
+    fn cube(min: (f32, f32, f32), max: (f32, f32, f32)) -> Cube {
+        Cube { min: Vec3::new(min.0, min.1, min.2), max: Vec3::new(max.0, max.1, max.2), material: gray(), uv_tile: None }
+    }
+
+    fn kinds(objects: &[Cube]) -> Vec<(usize, IssueKind)> {
+        validate_scene(objects, 0.5).into_iter().map(|issue| (issue.index, issue.kind)).collect()
+    }
+
+    #[test]
+    fn reports_each_kind_of_issue() {
+        let mut untextured = cube((5.0, 0.0, 0.0), (5.5, 0.5, 0.5));
+        untextured.material.diffuse = None;
+        let objects = [
+            cube((0.0, 0.0, 0.0), (0.5, 0.5, 0.5)),
+            cube((1.0, 0.0, 0.0), (0.5, 0.5, 0.5)),
+            cube((2.0, 0.0, 0.0), (2.0, 0.5, 0.5)),
+            cube((3.1, 0.0, 0.0), (3.6, 0.5, 0.5)),
+            untextured,
+        ];
+
+        assert_eq!(kinds(&objects), vec![
+            (1, IssueKind::Inverted),
+            (2, IssueKind::ZeroVolume),
+            (3, IssueKind::OffGrid),
+            (4, IssueKind::MissingTexture),
+        ]);
+    }
+
+    #[test]
+    fn duplicates_and_overlaps_are_told_apart() {
+        let objects = [
+            cube((0.0, 0.0, 0.0), (1.0, 1.0, 1.0)),
+            cube((0.0, 0.0, 0.0), (1.0, 1.0, 1.0)),
+            cube((0.5, 0.5, 0.5), (1.5, 1.5, 1.5)),
+            // Solo se tocan por una cara: no cuenta como superposición
+            cube((1.0, 0.0, 0.0), (1.5, 0.5, 0.5)),
+        ];
+
+        assert_eq!(kinds(&objects), vec![
+            (1, IssueKind::Duplicate { other: 0 }),
+            (2, IssueKind::Overlap { other: 0 }),
+            (2, IssueKind::Overlap { other: 1 }),
+        ]);
+    }
+
+    #[test]
+    fn sweep_keeps_comparing_long_boxes() {
+        // La caja larga se cruza con la tercera aunque la segunda, entre ambas en X, no la toque
+        let objects = [
+            cube((0.0, 0.0, 0.0), (5.0, 0.5, 0.5)),
+            cube((1.0, 2.0, 0.0), (1.5, 2.5, 0.5)),
+            cube((4.0, 0.0, 0.0), (4.5, 0.5, 0.5)),
+        ];
+
+        assert_eq!(kinds(&objects), vec![(2, IssueKind::Overlap { other: 0 })]);
+    }
+
+    #[test]
+    fn diorama_is_valid() {
+        let issues = validate_scene(&generate_diorama(), crate::constants::BLOCK_SIZE);
+        assert!(issues.is_empty(), "{}", issues.iter().map(|issue| issue.to_string()).collect::<Vec<_>>().join("\n"));
+    }
+}
@@ -0,0 +1,422 @@
+use nalgebra_glm::Vec3;
+use std::collections::HashMap;
+use crate::cube::Cube;
+use crate::material::Material;
+use crate::ray_intersect::{edge_normal, face_distance, Intersect, SceneIntersect, EDGE_EPSILON};
+use crate::texture::calculate_uv;
+
+pub const CHUNK_SIZE: i32 = 16;
+const CHUNK_VOLUME: usize = (CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE) as usize;
+
+// Celda entera del mundo y coordenada de un chunk
+pub type BlockPos = (i32, i32, i32);
+type ChunkPos = (i32, i32, i32);
+
+// Índice dentro de la paleta de materiales; 0 es aire
+type BlockId = u16;
+const AIR: BlockId = 0;
+
+// Bloque de 16x16x16 celdas; solo existe mientras tenga algún bloque sólido
+struct Chunk {
+    blocks: Box<[BlockId; CHUNK_VOLUME]>,
+    count: usize,  // Bloques que no son aire
+}
+
+impl Chunk {
+    fn new() -> Self {
+        Chunk { blocks: Box::new([AIR; CHUNK_VOLUME]), count: 0 }
+    }
+}
+
+/// Mundo de bloques disperso, dividido en chunks de 16x16x16 guardados por coordenada.
+/// Los materiales se guardan una sola vez en una paleta y cada celda solo guarda su índice.
+pub struct World {
+    pub block_size: f32,
+    chunks: HashMap<ChunkPos, Chunk>,
+    palette: Vec<Material>,
+    bounds: Option<(ChunkPos, ChunkPos)>,  // Primer y último chunk cargado en cada eje (None si está vacío)
+}
+
+impl World {
+    pub fn new(block_size: f32) -> Self {
+        World { block_size, chunks: HashMap::new(), palette: Vec::new(), bounds: None }
+    }
+
+    // Construye el mundo a partir de una lista de cubos alineados a la cuadrícula.
+    // Las cajas de varios bloques se rellenan celda por celda; las cajas sin volumen se ignoran.
+    pub fn from_cubes(objects: &[Cube], block_size: f32) -> Self {
+        let mut world = World::new(block_size);
+
+        for cube in objects {
+            let min = cube.min / block_size;
+            let max = cube.max / block_size;
+            let lo = |v: f32| (v + 1e-4).floor() as i32;
+            let hi = |v: f32| (v - 1e-4).ceil() as i32;
+
+            for x in lo(min.x)..hi(max.x) {
+                for y in lo(min.y)..hi(max.y) {
+                    for z in lo(min.z)..hi(max.z) {
+                        world.set((x, y, z), Some(&cube.material));
+                    }
+                }
+            }
+        }
+
+        world
+    }
+
+    pub fn get(&self, pos: BlockPos) -> Option<&Material> {
+        let id = self.block_id(pos);
+        if id == AIR {
+            None
+        } else {
+            Some(&self.palette[id as usize - 1])
+        }
+    }
+
+    // Coloca un bloque (o aire con `None`); los chunks vacíos se liberan
+    pub fn set(&mut self, pos: BlockPos, material: Option<&Material>) {
+        let id = match material {
+            Some(material) => self.material_id(material),
+            None => AIR,
+        };
+
+        let (chunk_pos, index) = split(pos);
+        if id == AIR {
+            if let Some(chunk) = self.chunks.get_mut(&chunk_pos) {
+                if chunk.blocks[index] != AIR {
+                    chunk.blocks[index] = AIR;
+                    chunk.count -= 1;
+                }
+                if chunk.count == 0 {
+                    self.chunks.remove(&chunk_pos);
+                    // Solo al liberar un chunk hay que volver a recorrerlos todos
+                    self.bounds = chunk_bounds(self.chunks.keys().copied());
+                }
+            }
+            return;
+        }
+
+        self.bounds = chunk_bounds(self.bounds.into_iter().flat_map(|(lo, hi)| [lo, hi]).chain([chunk_pos]));
+        let chunk = self.chunks.entry(chunk_pos).or_insert_with(Chunk::new);
+        if chunk.blocks[index] == AIR {
+            chunk.count += 1;
+        }
+        chunk.blocks[index] = id;
+    }
+
+    // Número de bloques sólidos y de chunks en memoria
+    pub fn block_count(&self) -> usize {
+        self.chunks.values().map(|chunk| chunk.count).sum()
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    // Recorre todos los bloques que no son aire
+    #[allow(dead_code)]
+    pub fn blocks(&self) -> impl Iterator<Item = (BlockPos, &Material)> + '_ {
+        self.chunks.iter().flat_map(move |(&(cx, cy, cz), chunk)| {
+            chunk.blocks.iter().enumerate().filter(|(_, &id)| id != AIR).map(move |(index, &id)| {
+                let index = index as i32;
+                let pos = (
+                    cx * CHUNK_SIZE + index % CHUNK_SIZE,
+                    cy * CHUNK_SIZE + index / (CHUNK_SIZE * CHUNK_SIZE),
+                    cz * CHUNK_SIZE + (index / CHUNK_SIZE) % CHUNK_SIZE,
+                );
+                (pos, &self.palette[id as usize - 1])
+            })
+        })
+    }
+
+    fn block_id(&self, pos: BlockPos) -> BlockId {
+        let (chunk_pos, index) = split(pos);
+        self.chunks.get(&chunk_pos).map_or(AIR, |chunk| chunk.blocks[index])
+    }
+
+    // Busca el material en la paleta y lo agrega si es nuevo
+    fn material_id(&mut self, material: &Material) -> BlockId {
+        let index = match self.palette.iter().position(|m| m == material) {
+            Some(index) => index,
+            None => {
+                self.palette.push(material.clone());
+                self.palette.len() - 1
+            }
+        };
+        (index + 1) as BlockId
+    }
+
+    // Caja que contiene todos los chunks cargados, en coordenadas del mundo
+    fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        let (lo, hi) = self.bounds?;
+        let scale = CHUNK_SIZE as f32 * self.block_size;
+        Some((
+            Vec3::new(lo.0 as f32, lo.1 as f32, lo.2 as f32) * scale,
+            Vec3::new((hi.0 + 1) as f32, (hi.1 + 1) as f32, (hi.2 + 1) as f32) * scale,
+        ))
+    }
+
+    // Recorre las celdas que cruza el rayo (Amanatides-Woo) y devuelve el primer bloque sólido.
+    // `tests` cuenta las celdas revisadas
+    fn traverse(&self, ray_origin: &Vec3, ray_direction: &Vec3, max_distance: f32, tests: &mut u32) -> Option<(BlockPos, usize, f32)> {
+        let (min, max) = self.bounds()?;
+        let size = self.block_size;
+        let inv_dir = Vec3::new(1.0 / ray_direction.x, 1.0 / ray_direction.y, 1.0 / ray_direction.z);
+
+        // Recortamos el rayo contra la caja del mundo, recordando por qué eje entra
+        let mut t_enter = 0.0f32;
+        let mut t_exit = max_distance;
+        let mut entry_axis = None;  // None si el origen ya está dentro de la caja
+        for axis in 0..3 {
+            let t1 = (min[axis] - ray_origin[axis]) * inv_dir[axis];
+            let t2 = (max[axis] - ray_origin[axis]) * inv_dir[axis];
+            if t1.min(t2) > t_enter {
+                t_enter = t1.min(t2);
+                entry_axis = Some(axis);
+            }
+            t_exit = t_exit.min(t1.max(t2));
+        }
+        if t_exit - t_enter < EDGE_EPSILON {
+            return None;
+        }
+
+        let start = ray_origin + ray_direction * t_enter;
+        let (lo, hi) = (min / size, max / size);
+        let mut cell = [0i32; 3];
+        let mut step = [0i32; 3];
+        let mut t_max = [f32::INFINITY; 3];
+        for axis in 0..3 {
+            // Sobre una cara de la caja la celda inicial tiene que quedar del lado de adentro
+            cell[axis] = ((start[axis] / size).floor() as i32).clamp(lo[axis].round() as i32, hi[axis].round() as i32 - 1);
+            if ray_direction[axis] > 0.0 {
+                step[axis] = 1;
+                t_max[axis] = ((cell[axis] + 1) as f32 * size - ray_origin[axis]) * inv_dir[axis];
+            } else if ray_direction[axis] < 0.0 {
+                step[axis] = -1;
+                t_max[axis] = (cell[axis] as f32 * size - ray_origin[axis]) * inv_dir[axis];
+            }
+        }
+
+        // Un rayo contenido en un plano de la cuadrícula pasa entre dos filas de celdas y toca
+        // las dos, como toca las caras de dos cubos vecinos: también se mira la celda de abajo
+        let mut twins = vec![(0, 0, 0)];
+        for axis in 0..3 {
+            let t = start[axis] / size;
+            if ray_direction[axis] == 0.0 && (t - t.round()).abs() < EDGE_EPSILON / size {
+                let mut offset = [0; 3];
+                offset[axis] = -1;
+                let shifted: Vec<BlockPos> = twins.iter().map(|&(x, y, z)| (x + offset[0], y + offset[1], z + offset[2])).collect();
+                twins.extend(shifted);
+            }
+        }
+
+        let mut t = t_enter;
+
+        while t <= t_exit {
+            // Como en `Cube`, una celda que el rayo apenas roza no cuenta
+            let chord = t_max[0].min(t_max[1]).min(t_max[2]) - t;
+            if let (Some(axis), true) = (entry_axis, chord >= EDGE_EPSILON) {
+                *tests += 1;
+                let solid = twins.iter()
+                    .map(|&(x, y, z)| (cell[0] + x, cell[1] + y, cell[2] + z))
+                    .find(|&pos| self.block_id(pos) != AIR);
+                if let Some(pos) = solid {
+                    return Some((pos, axis, t));
+                }
+            }
+
+            // Avanzamos por el eje cuyo próximo plano está más cerca
+            let axis = if t_max[0] < t_max[1] {
+                if t_max[0] < t_max[2] { 0 } else { 2 }
+            } else if t_max[1] < t_max[2] { 1 } else { 2 };
+
+            t = t_max[axis];
+            cell[axis] += step[axis];
+            t_max[axis] = ((cell[axis] + if step[axis] > 0 { 1 } else { 0 }) as f32 * size - ray_origin[axis]) * inv_dir[axis];
+            entry_axis = Some(axis);
+        }
+
+        None
+    }
+}
+
+impl SceneIntersect for World {
+    fn closest_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3, max_distance: f32) -> Intersect {
+        let mut tests = 0;
+        let Some((pos, axis, distance)) = self.traverse(ray_origin, ray_direction, max_distance, &mut tests) else {
+            return Intersect { tests, ..Intersect::empty() };
+        };
+
+        let size = self.block_size;
+        let point = ray_origin + ray_direction * distance;
+
+        let mut normal = Vec3::zeros();
+        normal[axis] = -ray_direction[axis].signum();
+
+        // Sobre una arista de la cuadrícula, la misma regla que la lista de cubos
+        let axes: Vec<usize> = (0..3).filter(|&other| {
+            let t = point[other] / size;
+            other == axis || (ray_direction[other] != 0.0 && (t - t.round()).abs() < EDGE_EPSILON / size)
+        }).collect();
+        let pos = if axes.len() > 1 {
+            let cell = |q: &Vec3| ((q.x / size).floor() as i32, (q.y / size).floor() as i32, (q.z / size).floor() as i32);
+            let (edge, owner) = edge_normal(&point, ray_direction, &axes, |q| self.block_id(cell(q)) != AIR);
+            normal = edge;
+            if self.block_id(cell(&owner)) != AIR { cell(&owner) } else { pos }
+        } else {
+            pos
+        };
+
+        let min = Vec3::new(pos.0 as f32, pos.1 as f32, pos.2 as f32) * size;
+        let max = min + Vec3::new(size, size, size);
+        let distance = face_distance(ray_origin, ray_direction, &normal, &min, &max);
+        let point = ray_origin + ray_direction * distance;
+        let uv = calculate_uv(point, normal, min, max, None);
+        // No hay lista de cubos: el identificador mezcla las coordenadas de la celda
+        let object = (pos.0 as u32).wrapping_mul(73_856_093) ^ (pos.1 as u32).wrapping_mul(19_349_663) ^ (pos.2 as u32).wrapping_mul(83_492_791);
+        Intersect { object: object as usize, tests, ..Intersect::new(point, normal, distance, uv, self.get(pos).unwrap().clone()) }
+    }
+
+    fn occluded(&self, ray_origin: &Vec3, ray_direction: &Vec3, max_distance: f32) -> bool {
+        self.traverse(ray_origin, ray_direction, max_distance, &mut 0).is_some()
+    }
+
+    fn pick(&self, ray_origin: &Vec3, ray_direction: &Vec3, max_distance: f32) -> Option<Cube> {
+        let (pos, _, _) = self.traverse(ray_origin, ray_direction, max_distance, &mut 0)?;
+
+        let size = self.block_size;
+        let min = Vec3::new(pos.0 as f32, pos.1 as f32, pos.2 as f32) * size;
+        Some(Cube {
+            min,
+            max: min + Vec3::new(size, size, size),
+            material: self.get(pos)?.clone(),
+            uv_tile: None,
+        })
+    }
+}
+
+// Primer y último chunk en cada eje de una lista de coordenadas de chunks
+fn chunk_bounds(mut keys: impl Iterator<Item = ChunkPos>) -> Option<(ChunkPos, ChunkPos)> {
+    let first = keys.next()?;
+    Some(keys.fold((first, first), |(lo, hi), (x, y, z)| {
+        ((lo.0.min(x), lo.1.min(y), lo.2.min(z)), (hi.0.max(x), hi.1.max(y), hi.2.max(z)))
+    }))
+}
+
+// Separa una celda del mundo en su chunk y el índice dentro de él
+fn split(pos: BlockPos) -> (ChunkPos, usize) {
+    let chunk_pos = (pos.0.div_euclid(CHUNK_SIZE), pos.1.div_euclid(CHUNK_SIZE), pos.2.div_euclid(CHUNK_SIZE));
+    let (x, y, z) = (pos.0.rem_euclid(CHUNK_SIZE), pos.1.rem_euclid(CHUNK_SIZE), pos.2.rem_euclid(CHUNK_SIZE));
+    (chunk_pos, (x + z * CHUNK_SIZE + y * CHUNK_SIZE * CHUNK_SIZE) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::{generate_terrain, TerrainParams};
+
+    #[test]
+    fn set_and_get_round_trip() {
+        let mut world = World::new(0.5);
+        let (stone, dirt) = (Material::stone(), Material::dirt());
+        world.set((0, 0, 0), Some(&stone));
+        world.set((-1, 20, 5), Some(&dirt));
+        world.set((0, 0, 0), Some(&dirt));
+
+        assert_eq!(world.get((0, 0, 0)), Some(&dirt));
+        assert_eq!(world.get((-1, 20, 5)), Some(&dirt));
+        assert_eq!(world.get((1, 0, 0)), None);
+        assert_eq!(world.block_count(), 2);
+        // Las coordenadas negativas caen en su propio chunk
+        assert_eq!(world.chunk_count(), 2);
+    }
+
+    #[test]
+    fn blocks_lists_every_solid_cell() {
+        let mut world = World::new(0.5);
+        let stone = Material::stone();
+        let cells = [(0, 0, 0), (15, 3, 7), (16, 0, 0), (-3, -17, 2)];
+        for pos in cells {
+            world.set(pos, Some(&stone));
+        }
+
+        let mut listed: Vec<BlockPos> = world.blocks().map(|(pos, material)| {
+            assert_eq!(material, &stone);
+            pos
+        }).collect();
+        listed.sort();
+        let mut expected = cells.to_vec();
+        expected.sort();
+        assert_eq!(listed, expected);
+    }
+
+    #[test]
+    fn clearing_the_last_block_frees_the_chunk() {
+        let mut world = World::new(0.5);
+        let stone = Material::stone();
+        world.set((1, 1, 1), Some(&stone));
+        world.set((2, 1, 1), Some(&stone));
+        world.set((40, 0, 0), Some(&stone));
+        assert_eq!(world.chunk_count(), 2);
+
+        world.set((1, 1, 1), None);
+        assert_eq!(world.chunk_count(), 2);
+        world.set((2, 1, 1), None);
+        assert_eq!(world.chunk_count(), 1);
+        assert_eq!(world.block_count(), 1);
+        // La caja del mundo se achica al chunk que queda
+        assert_eq!(world.bounds, Some(((2, 0, 0), (2, 0, 0))));
+
+        world.set((40, 0, 0), None);
+        assert_eq!(world.chunk_count(), 0);
+        assert!(world.bounds().is_none());
+    }
+
+    // El recorrido por celdas tiene que ver lo mismo que probar cubo por cubo, también en las
+    // aristas, donde antes uno devolvía la cara compartida entre dos bloques y el otro la visible
+    #[test]
+    fn traverse_matches_cube_list() {
+        let objects = generate_terrain(&TerrainParams { seed: 42, width: 14, depth: 14, ..TerrainParams::default() });
+        let world = World::from_cubes(&objects, 0.5);
+        let origin = Vec3::new(3.5, 9.0, -4.0);
+
+        for i in 0..140 {
+            for j in 0..100 {
+                let target = Vec3::new(i as f32 * 0.05, 0.0, j as f32 * 0.07);
+                let direction = (target - origin).normalize();
+                let cubes = <[Cube] as SceneIntersect>::closest_intersect(&objects, &origin, &direction, 20.0);
+                let chunks = world.closest_intersect(&origin, &direction, 20.0);
+
+                assert_eq!(cubes.is_intersecting, chunks.is_intersecting, "rayo hacia {:?}", target);
+                if cubes.is_intersecting {
+                    assert_eq!(cubes.point, chunks.point, "rayo hacia {:?}", target);
+                    assert_eq!(cubes.normal, chunks.normal, "rayo hacia {:?}", target);
+                    assert_eq!(cubes.uv, chunks.uv, "rayo hacia {:?}", target);
+                    assert_eq!(cubes.material, chunks.material, "rayo hacia {:?}", target);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn seam_between_blocks_shows_the_visible_face() {
+        // Dos bloques uno al lado del otro; el rayo pega justo en la arista de arriba que comparten
+        let stone = Material::stone();
+        let objects: Vec<Cube> = [0.0, 0.5].iter().map(|&x| Cube {
+            min: Vec3::new(x, 0.0, 0.0),
+            max: Vec3::new(x + 0.5, 0.5, 0.5),
+            material: stone.clone(),
+            uv_tile: None,
+        }).collect();
+        let world = World::from_cubes(&objects, 0.5);
+        let origin = Vec3::new(1.5, 1.5, 0.25);
+        let direction = Vec3::new(-1.0, -1.0, 0.0).normalize();
+
+        for hit in [objects.closest_intersect(&origin, &direction, 20.0), world.closest_intersect(&origin, &direction, 20.0)] {
+            assert!(hit.is_intersecting);
+            // La cara x = 0.5 entre los dos bloques no se ve; la de arriba sí
+            assert_eq!(hit.normal, Vec3::new(0.0, 1.0, 0.0));
+        }
+    }
+}